@@ -0,0 +1,202 @@
+//! Conversion between raw ADC samples and calibrated picoamperes.
+//!
+//! Reads store their signal as raw `i16` ADC values, with a [`Calibration`] mapping them to pA as
+//! `pA = (adc + offset) * scale`. Signal that only exists in pA, such as simulated signal, is
+//! quantised back to ADC values with [`Calibration::quantise`], which reports how many samples
+//! did not fit the run's ADC range in [`ClippingStats`].
+//!
+//! Large buffers are converted to pA with [`Calibration::to_pa_into`], which is vectorised, using
+//! AVX2 on x86_64 processors that support it.
+
+use crate::{run_info::RunInfoData, validation::ValidationError};
+
+/// The mapping of a read's ADC samples to pA, `pA = (adc + offset) * scale`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    pub offset: f32,
+    pub scale: f32,
+}
+
+/// How many samples were out of range when quantising pA signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ClippingStats {
+    /// Number of samples quantised.
+    pub samples: usize,
+    /// Samples below the ADC range, saturated to `adc_min`.
+    pub below: usize,
+    /// Samples above the ADC range, saturated to `adc_max`.
+    pub above: usize,
+    /// NaN samples, written as the ADC value nearest 0 pA.
+    pub nan: usize,
+}
+
+impl ClippingStats {
+    /// Samples that could not be represented, saturated or NaN.
+    pub fn clipped(&self) -> usize {
+        self.below + self.above + self.nan
+    }
+
+    /// Fraction of samples that could not be represented, zero for no samples.
+    pub fn clipped_fraction(&self) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            self.clipped() as f64 / self.samples as f64
+        }
+    }
+}
+
+impl Calibration {
+    /// A calibration with a finite offset and a finite, non-zero scale.
+    pub fn new(offset: f32, scale: f32) -> Result<Self, ValidationError> {
+        let mut problems = vec![];
+        if !scale.is_finite() || scale == 0.0 {
+            problems.push(format!(
+                "calibration scale must be finite and non-zero, got {scale}"
+            ));
+        }
+        if !offset.is_finite() {
+            problems.push(format!("calibration offset must be finite, got {offset}"));
+        }
+        if problems.is_empty() {
+            Ok(Calibration { offset, scale })
+        } else {
+            Err(ValidationError { problems })
+        }
+    }
+
+    /// The calibration spreading a run's `[adc_min, adc_max]` over `[pa_min, pa_max]`, so the pA range
+    /// is represented at the finest precision the ADC range allows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::calibration::Calibration;
+    /// use podders::run_info::RunInfoData;
+    ///
+    /// let run_info = RunInfoData::builder("acquisition_1").adc_range(-4096, 4095).build().unwrap();
+    /// let calibration = Calibration::from_pa_range(&run_info, -100.0, 400.0).unwrap();
+    /// assert!((calibration.to_pa(-4096) + 100.0).abs() < 1e-3);
+    /// assert!((calibration.to_pa(4095) - 400.0).abs() < 1e-3);
+    /// ```
+    pub fn from_pa_range(
+        run_info: &RunInfoData,
+        pa_min: f32,
+        pa_max: f32,
+    ) -> Result<Self, ValidationError> {
+        if !(pa_min.is_finite() && pa_max.is_finite() && pa_min < pa_max) {
+            return Err(ValidationError {
+                problems: vec![format!(
+                    "pA range must be finite and increasing, got [{pa_min}, {pa_max}]"
+                )],
+            });
+        }
+        if run_info.adc_min >= run_info.adc_max {
+            return Err(ValidationError {
+                problems: vec![format!(
+                    "adc_min ({}) must be less than adc_max ({})",
+                    run_info.adc_min, run_info.adc_max
+                )],
+            });
+        }
+        let adc_span = f32::from(run_info.adc_max) - f32::from(run_info.adc_min);
+        let scale = (pa_max - pa_min) / adc_span;
+        Calibration::new(pa_min / scale - f32::from(run_info.adc_min), scale)
+    }
+
+    /// A single ADC sample in pA.
+    pub fn to_pa(&self, adc: i16) -> f32 {
+        (f32::from(adc) + self.offset) * self.scale
+    }
+
+    /// ADC samples in pA.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::calibration::Calibration;
+    ///
+    /// let calibration = Calibration::new(10.0, 0.5).unwrap();
+    /// assert_eq!(calibration.to_pa_vec(&[-10, 0, 10]), vec![0.0, 5.0, 10.0]);
+    /// ```
+    pub fn to_pa_vec(&self, adc: &[i16]) -> Vec<f32> {
+        let mut pa = vec![0.0; adc.len()];
+        self.to_pa_into(adc, &mut pa);
+        pa
+    }
+
+    /// Convert ADC samples to pA into a buffer of the same length, each sample as [`Calibration::to_pa`].
+    ///
+    /// # Panics
+    ///
+    /// If `adc` and `pa` differ in length.
+    pub fn to_pa_into(&self, adc: &[i16], pa: &mut [f32]) {
+        assert_eq!(
+            adc.len(),
+            pa.len(),
+            "ADC and pA buffers must be the same length"
+        );
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: the processor supports AVX2, checked above
+            unsafe { to_pa_avx2(adc, pa, self.offset, self.scale) };
+            return;
+        }
+        to_pa(adc, pa, self.offset, self.scale);
+    }
+
+    /// Quantise pA samples to the nearest ADC values, saturating to `[adc_min, adc_max]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::calibration::Calibration;
+    ///
+    /// let calibration = Calibration::new(10.0, 0.5).unwrap();
+    /// let (signal, clipping) = calibration.quantise(&[5.0, 5.2, 5.3, 100.0, f32::NAN], -20, 20);
+    /// assert_eq!(signal, vec![0, 0, 1, 20, -10]);
+    /// assert_eq!((clipping.above, clipping.nan), (1, 1));
+    /// ```
+    pub fn quantise(&self, pa: &[f32], adc_min: i16, adc_max: i16) -> (Vec<i16>, ClippingStats) {
+        let mut clipping = ClippingStats {
+            samples: pa.len(),
+            ..Default::default()
+        };
+        let (min, max) = (f32::from(adc_min), f32::from(adc_max));
+        let zero = (-self.offset).round().clamp(min, max) as i16;
+        let signal = pa
+            .iter()
+            .map(|&pa| {
+                let adc = (pa / self.scale - self.offset).round();
+                if adc.is_nan() {
+                    clipping.nan += 1;
+                    zero
+                } else if adc < min {
+                    clipping.below += 1;
+                    adc_min
+                } else if adc > max {
+                    clipping.above += 1;
+                    adc_max
+                } else {
+                    adc as i16
+                }
+            })
+            .collect();
+        (signal, clipping)
+    }
+}
+
+/// Convert ADC samples to pA, written so the compiler vectorises it for the enabled target features.
+#[inline(always)]
+fn to_pa(adc: &[i16], pa: &mut [f32], offset: f32, scale: f32) {
+    for (pa, &adc) in pa.iter_mut().zip(adc) {
+        *pa = (f32::from(adc) + offset) * scale;
+    }
+}
+
+/// [`to_pa`] compiled for AVX2, converting eight samples per instruction.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn to_pa_avx2(adc: &[i16], pa: &mut [f32], offset: f32, scale: f32) {
+    to_pa(adc, pa, offset, scale)
+}
@@ -0,0 +1,343 @@
+//! Encodings of the `signal` column of the signal table.
+//!
+//! A [`SignalCodec`] turns each row of samples into the stored column type and back, and identifies
+//! itself to readers through the `ARROW:extension:name` of the signal field. [`Uncompressed`] and
+//! [`Vbz`] follow the encodings written by MinKNOW, and [`LossyVbz`] trades precision for space; other codecs can be plugged into the writer with
+//! `WriterOptions::signal_codec`, and into the reader with `Pod5Reader::register_codec`.
+
+use arrow::{
+    array::{Array, ArrayRef, Int16Array, LargeBinaryArray, LargeListArray},
+    buffer::OffsetBuffer,
+    datatypes::{DataType, Field},
+};
+use std::{collections::HashMap, error::Error, fmt, sync::Arc};
+
+/// Schema metadata key of an Arrow extension type's name.
+pub const EXTENSION_NAME_KEY: &str = "ARROW:extension:name";
+/// Schema metadata key of an Arrow extension type's metadata.
+pub const EXTENSION_METADATA_KEY: &str = "ARROW:extension:metadata";
+
+/// Encodes and decodes rows of the signal column.
+pub trait SignalCodec: fmt::Debug + Send + Sync {
+    /// Recorded as the extension name of the signal field, which readers pick the codec by.
+    /// `None` for a column readers understand from its type alone.
+    fn extension_name(&self) -> Option<&str>;
+
+    /// Recorded as the extension metadata of the signal field, e.g. parameters of the codec.
+    fn extension_metadata(&self) -> String {
+        String::new()
+    }
+
+    /// Low order bits of each sample lost by encoding, zero for lossless codecs.
+    fn dropped_bits(&self) -> u32 {
+        0
+    }
+
+    /// Arrow type of the stored signal column.
+    fn data_type(&self) -> DataType;
+
+    /// Encode the samples of one row, as a single row array of `data_type`.
+    fn encode(&self, samples: &Int16Array) -> Result<ArrayRef, Box<dyn Error>>;
+
+    /// Decode one row of a stored signal column, which holds `num_samples` samples.
+    fn decode(
+        &self,
+        column: &dyn Array,
+        row: usize,
+        num_samples: usize,
+    ) -> Result<Vec<i16>, Box<dyn Error>>;
+
+    /// The `signal` field of the signal table schema, stored with this codec.
+    fn field(&self) -> Field {
+        let field = Field::new("signal", self.data_type(), false);
+        match self.extension_name() {
+            Some(name) => field.with_metadata(HashMap::from([
+                (EXTENSION_NAME_KEY.to_string(), name.to_string()),
+                (
+                    EXTENSION_METADATA_KEY.to_string(),
+                    self.extension_metadata(),
+                ),
+            ])),
+            None => field,
+        }
+    }
+}
+
+/// Samples stored as is, in a `LargeList<Int16>`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Uncompressed;
+
+impl SignalCodec for Uncompressed {
+    fn extension_name(&self) -> Option<&str> {
+        None
+    }
+
+    fn data_type(&self) -> DataType {
+        DataType::LargeList(Arc::new(Field::new("item", DataType::Int16, true)))
+    }
+
+    fn encode(&self, samples: &Int16Array) -> Result<ArrayRef, Box<dyn Error>> {
+        // Shares the samples buffer, so the signal is not copied
+        Ok(Arc::new(LargeListArray::try_new(
+            Arc::new(Field::new("item", DataType::Int16, true)),
+            OffsetBuffer::from_lengths([samples.len()]),
+            Arc::new(samples.clone()),
+            None,
+        )?))
+    }
+
+    fn decode(
+        &self,
+        column: &dyn Array,
+        row: usize,
+        _num_samples: usize,
+    ) -> Result<Vec<i16>, Box<dyn Error>> {
+        let samples = column
+            .as_any()
+            .downcast_ref::<LargeListArray>()
+            .ok_or("signal column is not a large list")?
+            .value(row);
+        Ok(samples
+            .as_any()
+            .downcast_ref::<Int16Array>()
+            .ok_or("signal column is not a list of int16")?
+            .values()
+            .to_vec())
+    }
+}
+
+/// The pod5 VBZ encoding: zigzag delta coded samples, packed with svb16, then compressed with zstd.
+///
+/// Stored as `LargeBinary` with the `minknow.vbz` extension, as described by the pod5 format
+/// specification. It is only tested against this crate's own encoder and decoder, not yet against
+/// signal written by MinKNOW or the pod5 tools.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Vbz;
+
+/// The zstd level MinKNOW compresses VBZ signal with.
+const VBZ_ZSTD_LEVEL: i32 = 1;
+
+impl SignalCodec for Vbz {
+    fn extension_name(&self) -> Option<&str> {
+        Some("minknow.vbz")
+    }
+
+    fn data_type(&self) -> DataType {
+        DataType::LargeBinary
+    }
+
+    fn encode(&self, samples: &Int16Array) -> Result<ArrayRef, Box<dyn Error>> {
+        vbz_encode(samples.values())
+    }
+
+    fn decode(
+        &self,
+        column: &dyn Array,
+        row: usize,
+        num_samples: usize,
+    ) -> Result<Vec<i16>, Box<dyn Error>> {
+        vbz_decode(column, row, num_samples)
+    }
+}
+
+/// VBZ with the low order bits of each sample dropped first, in the manner of SLOW5's ex-zd.
+///
+/// Samples are rounded to the nearest multiple of `2^dropped_bits`, so each is off by at most
+/// half of that, and the remaining bits are stored as [`Vbz`] would. The dropped bits are recorded
+/// in the extension metadata of the signal field, as `dropped_bits=N`, which [`Pod5Reader`] reads
+/// back. The official pod5 tools cannot read this encoding.
+///
+/// [`Pod5Reader`]: crate::reader::Pod5Reader
+///
+/// # Examples
+///
+/// ```
+/// use arrow::array::Int16Array;
+/// use podders::codec::{LossyVbz, SignalCodec};
+///
+/// let codec = LossyVbz::new(2).unwrap();
+/// let encoded = codec.encode(&Int16Array::from(vec![100, 101, 102, -7])).unwrap();
+/// assert_eq!(codec.decode(&encoded, 0, 4).unwrap(), vec![100, 100, 104, -8]);
+/// assert!(LossyVbz::new(16).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LossyVbz {
+    dropped_bits: u32,
+}
+
+impl LossyVbz {
+    /// Extension name of the signal field when stored with this codec.
+    pub const EXTENSION_NAME: &'static str = "podders.lossy_vbz";
+
+    /// Drop `dropped_bits` low order bits of each sample, leaving at least one.
+    pub fn new(dropped_bits: u32) -> Result<Self, Box<dyn Error>> {
+        if dropped_bits >= i16::BITS {
+            return Err(format!(
+                "cannot drop {dropped_bits} bits of a {} bit sample",
+                i16::BITS
+            )
+            .into());
+        }
+        Ok(LossyVbz { dropped_bits })
+    }
+
+    /// The codec described by the extension metadata of a stored signal field.
+    fn from_metadata(metadata: &str) -> Result<Self, Box<dyn Error>> {
+        let dropped_bits = metadata
+            .strip_prefix("dropped_bits=")
+            .and_then(|bits| bits.parse().ok())
+            .ok_or_else(|| format!("invalid {} metadata {metadata:?}", Self::EXTENSION_NAME))?;
+        Self::new(dropped_bits)
+    }
+}
+
+impl SignalCodec for LossyVbz {
+    fn extension_name(&self) -> Option<&str> {
+        Some(Self::EXTENSION_NAME)
+    }
+
+    fn extension_metadata(&self) -> String {
+        format!("dropped_bits={}", self.dropped_bits)
+    }
+
+    fn dropped_bits(&self) -> u32 {
+        self.dropped_bits
+    }
+
+    fn data_type(&self) -> DataType {
+        DataType::LargeBinary
+    }
+
+    fn encode(&self, samples: &Int16Array) -> Result<ArrayRef, Box<dyn Error>> {
+        let bits = self.dropped_bits;
+        let half = (1i32 << bits) >> 1;
+        let truncated: Vec<i16> = samples
+            .values()
+            .iter()
+            // Cannot overflow, as the shift frees more room than rounding up takes
+            .map(|&sample| ((i32::from(sample) + half) >> bits) as i16)
+            .collect();
+        vbz_encode(&truncated)
+    }
+
+    fn decode(
+        &self,
+        column: &dyn Array,
+        row: usize,
+        num_samples: usize,
+    ) -> Result<Vec<i16>, Box<dyn Error>> {
+        let mut samples = vbz_decode(column, row, num_samples)?;
+        for sample in samples.iter_mut() {
+            // Rounding up may land one step past i16::MAX
+            *sample = (i32::from(*sample) << self.dropped_bits).min(i16::MAX.into()) as i16;
+        }
+        Ok(samples)
+    }
+}
+
+/// Encode one row of samples as VBZ.
+fn vbz_encode(samples: &[i16]) -> Result<ArrayRef, Box<dyn Error>> {
+    let packed = svb16_encode(samples);
+    let compressed = zstd::bulk::compress(&packed, VBZ_ZSTD_LEVEL)?;
+    Ok(Arc::new(LargeBinaryArray::from_vec(vec![&compressed])))
+}
+
+/// Decode one row of a VBZ signal column.
+fn vbz_decode(
+    column: &dyn Array,
+    row: usize,
+    num_samples: usize,
+) -> Result<Vec<i16>, Box<dyn Error>> {
+    let compressed = column
+        .as_any()
+        .downcast_ref::<LargeBinaryArray>()
+        .ok_or("signal column is not large binary")?
+        .value(row);
+    let packed = zstd::bulk::decompress(compressed, svb16_max_encoded_len(num_samples))?;
+    svb16_decode(&packed, num_samples)
+}
+
+/// The most bytes svb16 packs `count` samples into: one key bit and up to two data bytes each.
+fn svb16_max_encoded_len(count: usize) -> usize {
+    count.div_ceil(8) + 2 * count
+}
+
+/// Pack samples with svb16, zigzag delta coding them first.
+///
+/// The output is one key bit per sample, set when the sample needs two bytes rather than one,
+/// followed by the little endian data bytes.
+pub(crate) fn svb16_encode(samples: &[i16]) -> Vec<u8> {
+    let mut packed = vec![0; samples.len().div_ceil(8)];
+    packed.reserve(2 * samples.len());
+    let mut previous = 0i16;
+    for (i, &sample) in samples.iter().enumerate() {
+        let delta = sample.wrapping_sub(previous);
+        previous = sample;
+        let zigzag = ((delta << 1) ^ (delta >> 15)) as u16;
+        if zigzag > 0xff {
+            packed[i / 8] |= 1 << (i % 8);
+            packed.extend_from_slice(&zigzag.to_le_bytes());
+        } else {
+            packed.push(zigzag as u8);
+        }
+    }
+    packed
+}
+
+/// Unpack `count` samples packed with [`svb16_encode`].
+pub(crate) fn svb16_decode(packed: &[u8], count: usize) -> Result<Vec<i16>, Box<dyn Error>> {
+    let key_len = count.div_ceil(8);
+    if packed.len() < key_len {
+        return Err(format!("svb16 data is too short for {count} samples").into());
+    }
+    let (keys, data) = packed.split_at(key_len);
+    let mut data = data.iter();
+    let mut next = || data.next().copied().ok_or("svb16 data ended early");
+    let mut samples = Vec::with_capacity(count);
+    let mut previous = 0i16;
+    for i in 0..count {
+        let zigzag = if keys[i / 8] >> (i % 8) & 1 == 1 {
+            u16::from_le_bytes([next()?, next()?])
+        } else {
+            u16::from(next()?)
+        };
+        let delta = (zigzag >> 1) as i16 ^ -((zigzag & 1) as i16);
+        previous = previous.wrapping_add(delta);
+        samples.push(previous);
+    }
+    Ok(samples)
+}
+
+/// The codec of a stored signal field, from its extension name, out of the built-in and `registered` codecs.
+pub(crate) fn codec_for_field(
+    field: &Field,
+    registered: &[Arc<dyn SignalCodec>],
+) -> Result<Arc<dyn SignalCodec>, Box<dyn Error>> {
+    let name = field.metadata().get(EXTENSION_NAME_KEY).map(String::as_str);
+    let metadata = field
+        .metadata()
+        .get(EXTENSION_METADATA_KEY)
+        .map_or("", String::as_str);
+    let matches = |codec: &dyn SignalCodec| {
+        codec.extension_name() == name && codec.data_type() == *field.data_type()
+    };
+    if let Some(codec) = registered.iter().find(|codec| matches(codec.as_ref())) {
+        return Ok(codec.clone());
+    }
+    let builtin: Option<Arc<dyn SignalCodec>> = match name {
+        None => Some(Arc::new(Uncompressed)),
+        Some("minknow.vbz") => Some(Arc::new(Vbz)),
+        Some(LossyVbz::EXTENSION_NAME) => Some(Arc::new(LossyVbz::from_metadata(metadata)?)),
+        Some(_) => None,
+    };
+    builtin
+        .filter(|codec| matches(codec.as_ref()))
+        .ok_or_else(|| {
+            format!(
+                "no signal codec for {} signal, with extension {}",
+                field.data_type(),
+                name.unwrap_or("none")
+            )
+            .into()
+        })
+}
@@ -0,0 +1,231 @@
+//! Dictionaries of the `pore_type`, `end_reason` and `run_info` columns of the reads table.
+//!
+//! The Arrow IPC file format writes each dictionary once, before the first batch that uses it,
+//! and does not allow it to be replaced afterwards. So every batch of a reads table must share
+//! the same dictionaries. [`ReadDictionaries`] grows the dictionaries from pre-registered values
+//! and the reads seen before the first batch is written, then freezes them. A value that is not in
+//! a frozen dictionary is refused with a [`DictionaryError`], rather than corrupting the table.
+
+use arrow::{
+    array::{DictionaryArray, Int16Array, StringArray},
+    datatypes::Int16Type,
+};
+use std::{collections::HashMap, error::Error, fmt, sync::Arc};
+
+use crate::reads::{EndReason, PoreType, ReadInfo};
+
+/// Dictionary id of the `pore_type` column in the reads table schema.
+pub const PORE_TYPE_DICT_ID: i64 = 0;
+/// Dictionary id of the `end_reason` column in the reads table schema.
+pub const END_REASON_DICT_ID: i64 = 1;
+/// Dictionary id of the `run_info` column in the reads table schema.
+pub const RUN_INFO_DICT_ID: i64 = 2;
+
+/// A value that cannot be written to a dictionary column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DictionaryError {
+    /// Name of the dictionary column.
+    pub column: &'static str,
+    /// The value that could not be added.
+    pub value: String,
+    /// Why it could not be added.
+    pub reason: String,
+}
+
+impl fmt::Display for DictionaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cannot add {:?} to the {} dictionary: {}",
+            self.value, self.column, self.reason
+        )
+    }
+}
+
+impl Error for DictionaryError {}
+
+/// The values of a single dictionary column, in key order.
+#[derive(Debug, Clone)]
+struct Dictionary {
+    column: &'static str,
+    keys: HashMap<String, i16>,
+    values: Arc<StringArray>,
+}
+
+impl Dictionary {
+    fn new(column: &'static str) -> Self {
+        Dictionary {
+            column,
+            keys: HashMap::new(),
+            values: Arc::new(StringArray::from(Vec::<String>::new())),
+        }
+    }
+
+    fn key(&self, value: &str) -> Option<i16> {
+        self.keys.get(value).copied()
+    }
+
+    fn error(&self, value: &str, reason: &str) -> DictionaryError {
+        DictionaryError {
+            column: self.column,
+            value: value.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Check a value could be registered.
+    fn check(&self, value: &str, frozen: bool) -> Result<(), DictionaryError> {
+        if frozen && self.key(value).is_none() {
+            return Err(self.error(
+                value,
+                "the dictionaries are fixed once the first reads batch is written, \
+                 pre-register the value in the WriterOptions, or push its run info, before then",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Add a value if it is new, returning its key.
+    fn register(&mut self, value: &str, frozen: bool) -> Result<i16, DictionaryError> {
+        self.check(value, frozen)?;
+        if let Some(key) = self.key(value) {
+            return Ok(key);
+        }
+        let key = i16::try_from(self.keys.len()).map_err(|_| {
+            self.error(
+                value,
+                "the dictionary is full, with one value per int16 key",
+            )
+        })?;
+        self.keys.insert(value.to_string(), key);
+        // Rebuilt rather than appended to, as the array is shared with any batches built so far
+        let mut values: Vec<Option<&str>> = self.values.iter().collect();
+        values.push(Some(value));
+        self.values = Arc::new(StringArray::from(values));
+        Ok(key)
+    }
+
+    /// A single row column holding `value`, which must already be registered.
+    fn column(&self, value: &str) -> Result<DictionaryArray<Int16Type>, Box<dyn Error>> {
+        let key = self
+            .key(value)
+            .ok_or_else(|| format!("{value:?} is not in the {} dictionary", self.column))?;
+        Ok(DictionaryArray::try_new(
+            Int16Array::from(vec![key]),
+            self.values.clone(),
+        )?)
+    }
+}
+
+/// The dictionaries of a reads table, shared by every batch written to it.
+///
+/// The well known pore types and end reasons are registered up front.
+///
+/// # Examples
+///
+/// ```
+/// use podders::dictionary::ReadDictionaries;
+/// use podders::reads::{dummy_read_row, PoreType};
+///
+/// let mut dictionaries = ReadDictionaries::new();
+/// dictionaries.register_run_info("acquisition_1").unwrap();
+/// dictionaries.freeze();
+/// let mut read = dummy_read_row(None).unwrap();
+/// read.run_info = "acquisition_1".to_string();
+/// assert!(dictionaries.register_read(&read).is_ok());
+/// read.pore_type = PoreType::Other("RNA004".to_string());
+/// assert!(dictionaries.register_read(&read).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReadDictionaries {
+    pore_type: Dictionary,
+    end_reason: Dictionary,
+    run_info: Dictionary,
+    frozen: bool,
+}
+
+impl Default for ReadDictionaries {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadDictionaries {
+    pub fn new() -> Self {
+        let mut dictionaries = ReadDictionaries {
+            pore_type: Dictionary::new("pore_type"),
+            end_reason: Dictionary::new("end_reason"),
+            run_info: Dictionary::new("run_info"),
+            frozen: false,
+        };
+        for pore_type in [PoreType::NotSet, PoreType::R9, PoreType::R10] {
+            dictionaries
+                .register_pore_type(&pore_type)
+                .expect("a new dictionary has room");
+        }
+        for end_reason in EndReason::KNOWN {
+            dictionaries
+                .register_end_reason(&end_reason)
+                .expect("a new dictionary has room");
+        }
+        dictionaries
+    }
+
+    pub fn register_pore_type(&mut self, pore_type: &PoreType) -> Result<(), DictionaryError> {
+        self.pore_type
+            .register(&pore_type.to_string(), self.frozen)
+            .map(|_| ())
+    }
+
+    pub fn register_end_reason(&mut self, end_reason: &EndReason) -> Result<(), DictionaryError> {
+        self.end_reason
+            .register(&end_reason.to_string(), self.frozen)
+            .map(|_| ())
+    }
+
+    /// Register the `acquisition_id` of a run info, for reads to refer to.
+    pub fn register_run_info(&mut self, acquisition_id: &str) -> Result<(), DictionaryError> {
+        self.run_info
+            .register(acquisition_id, self.frozen)
+            .map(|_| ())
+    }
+
+    /// Register every dictionary value of a read. Once frozen, this checks the values are known.
+    pub fn register_read(&mut self, read: &ReadInfo) -> Result<(), DictionaryError> {
+        self.register_pore_type(&read.pore_type)?;
+        self.register_end_reason(&read.end_reason)?;
+        self.register_run_info(&read.run_info)
+    }
+
+    /// Check every dictionary value of a read could be registered, i.e. is known once frozen.
+    pub fn check_read(&self, read: &ReadInfo) -> Result<(), DictionaryError> {
+        self.pore_type
+            .check(&read.pore_type.to_string(), self.frozen)?;
+        self.end_reason
+            .check(&read.end_reason.to_string(), self.frozen)?;
+        self.run_info.check(&read.run_info, self.frozen)
+    }
+
+    /// Fix the dictionaries, as they have been written. Further values are refused.
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// The `pore_type`, `end_reason` and `run_info` columns of a single read row.
+    ///
+    /// Every value of the read must already be registered.
+    pub fn read_columns(
+        &self,
+        read: &ReadInfo,
+    ) -> Result<[Arc<DictionaryArray<Int16Type>>; 3], Box<dyn Error>> {
+        Ok([
+            Arc::new(self.pore_type.column(&read.pore_type.to_string())?),
+            Arc::new(self.end_reason.column(&read.end_reason.to_string())?),
+            Arc::new(self.run_info.column(&read.run_info)?),
+        ])
+    }
+}
@@ -10,7 +10,7 @@ use flatbuffers::WIPOffset;
 use uuid::Uuid;
 
 use crate::{
-    root_as_footer, ContentType, EmbeddedFile, EmbeddedFileArgs, Footer, FooterArgs, POD5_VERSION,
+    root_as_footer, ContentType, EmbeddedFile, EmbeddedFileArgs, Footer, FooterArgs, Pod5Version,
     SIGNATURE, SOFTWARE,
 };
 const FOOTER_MAGIC: &[u8; 8] = b"FOOTER\0\0";
@@ -19,22 +19,28 @@ pub struct FileInfo {
     pub length: u64,
 }
 
-pub fn read_pod5_footer(filename: &str, table: ContentType) -> FileInfo {
-    let mut file = File::open(filename).unwrap();
-    let _end = file.seek(SeekFrom::End(0)).unwrap();
-    file.seek(SeekFrom::Current(-32)).unwrap(); // Signature + Section marker + 8 bytes for footer length
+/// Read the raw flatbuffer footer from the end of an open pod5 file.
+pub(crate) fn read_footer_buffer(file: &mut File) -> std::io::Result<Vec<u8>> {
+    let _end = file.seek(SeekFrom::End(0))?;
+    file.seek(SeekFrom::Current(-32))?; // Signature + Section marker + 8 bytes for footer length
     let mut buffer = [0; 8]; // Buffer for 8 bytes
 
-    file.read_exact(&mut buffer).unwrap(); // Read 8 bytes
+    file.read_exact(&mut buffer)?; // Read 8 bytes
 
     // Convert bytes to little-endian i64
     let value = i64::from_le_bytes(buffer);
     // Seek to the footer position
-    file.seek(SeekFrom::Current(-(8 + value))).unwrap();
+    file.seek(SeekFrom::Current(-(8 + value)))?;
 
     // Read the footer data
     let mut buf = vec![0; value as usize];
-    file.read_exact(&mut buf).unwrap();
+    file.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+pub fn read_pod5_footer(filename: &str, table: ContentType) -> FileInfo {
+    let mut file = File::open(filename).unwrap();
+    let buf = read_footer_buffer(&mut file).unwrap();
 
     // Deserialize the FlatBuffer
     let footer = root_as_footer(&buf).unwrap();
@@ -55,12 +61,13 @@ pub fn write_flatbuffer_footer(
     embedded_args: Vec<&EmbeddedFileArgs>,
     file_identifer: Uuid,
     section_marker: &[u8],
+    pod5_version: Pod5Version,
 ) -> Result<(), Box<dyn Error>> {
     let mut builder = flatbuffers::FlatBufferBuilder::new();
     // metadata
     let file_identifier = builder.create_string(&file_identifer.to_string());
     let software = builder.create_string(SOFTWARE);
-    let pod5_version = builder.create_string(POD5_VERSION);
+    let pod5_version = builder.create_string(pod5_version.as_str());
     // Create EmbeddedFiles
     let mut embedded_files: Vec<WIPOffset<EmbeddedFile>> = Vec::new();
     for embedded in embedded_args {
@@ -3,7 +3,8 @@
 //! This library offers comprehensive functionalities for the Pod5 file format,
 //! focusing on data serialization with Apache Arrow and FlatBuffers.
 //! It includes capabilities to create, and write structured data in the Pod5 format,
-//! adhering to the format as of 20/12/23 (v0.3.2)
+//! adhering to the format as of 20/12/23 (v0.3.2) by default, or a newer version selected
+//! with [`Pod5Version`].
 //!
 //! Key Features:
 //! - Matches schemas for official pod5 specification.
@@ -12,27 +13,57 @@
 //! The library provides various submodules (`reads`, `run_info`, `signal`, `footer`)
 //! each dedicated to handling different aspects of the Pod5 file format.
 
-use arrow::datatypes::Schema;
-use arrow::ipc::writer::FileWriter;
+use arrow::array::Int16Array;
+use arrow::compute::concat_batches;
+use arrow::datatypes::{Field, Schema};
+use arrow::error::ArrowError;
+use arrow::ipc::writer::{FileWriter, IpcWriteOptions};
+use arrow::ipc::CompressionType;
 
 use arrow::record_batch::RecordBatch;
+use codec::{SignalCodec, Uncompressed};
+use dictionary::ReadDictionaries;
 use footer::write_flatbuffer_footer;
-use reads::{create_read_batches, create_reads_arrow_schema, ReadInfo};
+use log::debug;
+use reads::{
+    create_read_batches, create_reads_arrow_schema, EndReason, ExtensionType, PoreType, ReadInfo,
+    ReadInfoRef, SPEC_READ_FIELDS,
+};
 use run_info::{create_run_info_batch, run_info_schema, RunInfoData};
 use signal::signal_schema;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fmt;
 use std::fs::File;
 use std::io::{Seek, Write};
+use std::str::FromStr;
+use validation::{DuplicatePolicy, ValidationError, ValidationPolicy};
+pub mod calibration;
+pub mod codec;
+pub mod dictionary;
 pub mod footer;
+pub mod normalisation;
+pub mod reader;
 pub mod reads;
+pub mod resample;
 pub mod run_info;
+#[cfg(feature = "serde")]
+mod serde_helpers;
 pub mod signal;
+pub mod simulate;
+pub mod validation;
 use std::sync::Arc;
 use uuid::Uuid;
 extern crate flatbuffers;
 
 // import the generated code
-#[allow(dead_code, unused_imports)]
+#[allow(
+    dead_code,
+    unused_imports,
+    unknown_lints,
+    mismatched_lifetime_syntaxes,
+    clippy::all
+)]
 #[path = "../static/footer_generated.rs"]
 pub mod footer_generated;
 pub use footer_generated::minknow::reads_format::{
@@ -43,12 +74,166 @@ pub use footer_generated::minknow::reads_format::{
 /// Pod5 in hexadecimal - file signature
 const SIGNATURE: [u8; 8] = [0x8B, 0x50, 0x4F, 0x44, 0x0D, 0x0A, 0x1A, 0x0A];
 
-/// Pod5 version at time of writing
-const POD5_VERSION: &str = "0.3.2";
+/// Pod5 specification versions that can be written.
+///
+/// The version decides the fields of the reads table, and is recorded in the schema
+/// metadata of every table and in the footer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Pod5Version {
+    /// v0.3.2, the 21 field reads table. The version at time of writing.
+    #[default]
+    V0_3_2,
+    /// v0.3.10, which adds `open_pore_level` to the reads table.
+    V0_3_10,
+}
+
+impl Pod5Version {
+    /// The version string recorded in the file.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Pod5Version::V0_3_2 => "0.3.2",
+            Pod5Version::V0_3_10 => "0.3.10",
+        }
+    }
+
+    /// Whether the reads table has the `open_pore_level` field.
+    pub fn has_open_pore_level(&self) -> bool {
+        *self >= Pod5Version::V0_3_10
+    }
+
+    /// The supported version closest to `version`, e.g. to copy a file written by other tools.
+    ///
+    /// That is the newest supported version not newer than it, so there are no fields the file
+    /// lacks, or the oldest for older and unparsable versions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::Pod5Version;
+    ///
+    /// assert_eq!(Pod5Version::nearest("0.3.10"), Pod5Version::V0_3_10);
+    /// assert_eq!(Pod5Version::nearest("0.3.28"), Pod5Version::V0_3_10);
+    /// assert_eq!(Pod5Version::nearest("0.3.5"), Pod5Version::V0_3_2);
+    /// assert_eq!(Pod5Version::nearest("0.2.0"), Pod5Version::V0_3_2);
+    /// ```
+    pub fn nearest(version: &str) -> Pod5Version {
+        let parse = |version: &str| {
+            version
+                .split('.')
+                .map(|x| x.parse::<u64>().ok())
+                .collect::<Option<Vec<_>>>()
+        };
+        let Some(version) = parse(version) else {
+            return Pod5Version::default();
+        };
+        [Pod5Version::V0_3_10, Pod5Version::V0_3_2]
+            .into_iter()
+            .find(|supported| parse(supported.as_str()).is_some_and(|x| x <= version))
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Pod5Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Pod5Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0.3.2" => Ok(Pod5Version::V0_3_2),
+            "0.3.10" => Ok(Pod5Version::V0_3_10),
+            version => Err(format!("unsupported pod5 version {version}")),
+        }
+    }
+}
+
+/// Arrow IPC buffer compression of the reads and run info tables.
+///
+/// Independent of how the signal itself is stored. Readers of the file must support the
+/// codec, as the official pod5 readers do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableCompression {
+    /// Uncompressed, as written by MinKNOW.
+    #[default]
+    None,
+    Lz4Frame,
+    Zstd,
+}
+
+impl TableCompression {
+    /// Arrow IPC write options applying this compression.
+    fn ipc_write_options(&self) -> Result<IpcWriteOptions, ArrowError> {
+        IpcWriteOptions::default().try_with_compression(match self {
+            TableCompression::None => None,
+            TableCompression::Lz4Frame => Some(CompressionType::LZ4_FRAME),
+            TableCompression::Zstd => Some(CompressionType::ZSTD),
+        })
+    }
+}
 
 /// Podders version that wrote the file
 const SOFTWARE: &str = "PODDERS! v0.1.0";
 
+/// Creates the schema metadata the pod5 specification requires on every table.
+pub(crate) fn _schema_metadata(
+    file_identifier: &Uuid,
+    version: Pod5Version,
+) -> HashMap<String, String> {
+    let mut metadata: HashMap<String, String> = HashMap::new();
+    metadata.insert(
+        "MINKNOW:pod5_version".to_string(),
+        version.as_str().to_string(),
+    );
+    metadata.insert("MINKNOW:software".to_string(), SOFTWARE.to_string());
+    metadata.insert(
+        "MINKNOW:file_identifier".to_string(),
+        file_identifier.to_string(),
+    );
+    metadata
+}
+
+/// Adds user defined metadata to a table schema, refusing keys reserved by the pod5 or Arrow specifications.
+fn _with_custom_metadata(
+    schema: Schema,
+    custom_metadata: &HashMap<String, String>,
+) -> Result<Schema, ValidationError> {
+    let problems: Vec<String> = custom_metadata
+        .keys()
+        .filter(|key| key.starts_with(reader::MINKNOW_METADATA_PREFIX) || key.starts_with("ARROW:"))
+        .map(|key| format!("schema metadata key {key} uses a reserved prefix"))
+        .collect();
+    if !problems.is_empty() {
+        return Err(ValidationError { problems });
+    }
+    let mut metadata = schema.metadata().clone();
+    metadata.extend(custom_metadata.clone());
+    Ok(schema.with_metadata(metadata))
+}
+
+/// Appends the extension columns to a reads schema, refusing names that are taken.
+fn _with_extension_columns(
+    schema: Schema,
+    extension_columns: &[(String, ExtensionType)],
+) -> Result<Schema, ValidationError> {
+    let mut problems = vec![];
+    let mut fields: Vec<Field> = schema.fields().iter().map(|x| x.as_ref().clone()).collect();
+    for (name, extension_type) in extension_columns {
+        if SPEC_READ_FIELDS.contains(&name.as_str()) || fields.iter().any(|x| x.name() == name) {
+            problems.push(format!("extension column name {name} is already taken"));
+            continue;
+        }
+        fields.push(extension_type.field(name));
+    }
+    if !problems.is_empty() {
+        return Err(ValidationError { problems });
+    }
+    Ok(Schema::new_with_metadata(fields, schema.metadata().clone()))
+}
+
 /// Generates a unique section marker for a file.
 ///
 /// This function creates a new V4 UUID and converts it to a byte vector.
@@ -79,20 +264,24 @@ fn _generate_section_marker() {
 /// # Arguments
 /// * `file_handle` - A mutable reference to the file where the data will be written.
 /// * `section_marker` - A 16-byte array to mark the end of the written section.
+/// * `schema` - The schema of the table. This is always written, so a table with no batches is still valid.
 /// * `batches` - A vector of RecordBatches to be written to the file.
 /// * `embedded_file` - A mutable reference to an EmbeddedFileArgs struct to be updated with the new offset and length.
 ///
 /// # Returns
-/// A Result<(), std::io::Error>, indicating the success or failure of the operation.
+/// A Result<(), Box<dyn Error>>, with any IO or Arrow error from writing the table.
 ///
 /// # Example
 /// ```
 /// # use arrow::ipc::writer::FileWriter;
 /// # use arrow::record_batch::RecordBatch;
 /// # use std::fs::File;
+/// # use arrow::datatypes::{Field, Schema};
+/// # use std::sync::Arc;
 /// # use uuid::Uuid;
 /// # fn main() -> std::io::Result<()> {
-/// # let mut file_handle = File::create("example.arrow")?;
+/// # let mut file_handle = File::create(std::env::temp_dir().join("example.arrow"))?;
+/// # let schema = Arc::new(Schema::empty());
 /// let section_marker = Uuid::new_v4();
 /// let batches: Vec<RecordBatch> = vec![]; // Populate with actual RecordBatches
 /// let mut embedded_file = EmbeddedFileArgs {
@@ -102,11 +291,11 @@ fn _generate_section_marker() {
 ///     content_type: ContentType::ReadsTable,
 /// };
 ///
-/// _write_table(&mut file_handle, section_marker.as_bytes(), &batches, &mut embedded_file)?;
+/// _write_table(&mut file_handle, section_marker.as_bytes(), &schema, &batches, &mut embedded_file)?;
 /// // Now embedded_file contains updated offset and length
 /// # Ok(())
 /// # }
-/// # fn _write_table(file_handle: &mut File, section_marker: &[u8; 16], batches: &Vec<RecordBatch>, embedded_file: &mut EmbeddedFileArgs) -> std::io::Result<()> {
+/// # fn _write_table(file_handle: &mut File, section_marker: &[u8; 16], schema: &Arc<Schema>, batches: &Vec<RecordBatch>, embedded_file: &mut EmbeddedFileArgs) -> std::io::Result<()> {
 /// #     Ok(())
 /// # }
 /// # struct EmbeddedFileArgs {
@@ -121,23 +310,38 @@ fn _generate_section_marker() {
 fn _write_table(
     mut file_handle: &mut File,
     section_marker: &[u8; 16],
+    schema: &Arc<Schema>,
     batches: &Vec<RecordBatch>,
     embedded_file: &mut EmbeddedFileArgs,
-) -> Result<(), std::io::Error> {
-    let offset: i64 = file_handle.stream_position().unwrap() as i64;
-    // Assuming all RecordBatches have the same schema
-    let schema = batches[0].schema();
+    compression: TableCompression,
+) -> Result<(), Box<dyn Error>> {
+    embedded_file.offset = file_handle.stream_position()? as i64;
+    // Always use the known table schema, so tables with no rows are still written
     {
-        let mut writer = FileWriter::try_new(file_handle, &schema).unwrap();
+        let mut writer = FileWriter::try_new_with_options(
+            file_handle,
+            schema,
+            compression.ipc_write_options()?,
+        )?;
 
         for batch in batches {
-            writer.write(batch).expect("Failed to write batch to IPC");
+            writer.write(batch)?;
         }
 
-        writer.finish().unwrap();
-        file_handle = writer.into_inner().unwrap();
+        writer.finish()?;
+        file_handle = writer.into_inner()?;
     }
-    let length = file_handle.stream_position().unwrap() as i64 - offset; // Replace with actual length
+    Ok(_finish_table(file_handle, section_marker, embedded_file)?)
+}
+
+/// Record the length of a table written from `embedded_file.offset` up to the current position,
+/// then pad to 8 bytes and write the section marker after it.
+fn _finish_table(
+    file_handle: &mut File,
+    section_marker: &[u8; 16],
+    embedded_file: &mut EmbeddedFileArgs,
+) -> Result<(), std::io::Error> {
+    let length = file_handle.stream_position()? as i64 - embedded_file.offset;
 
     let current_pos = file_handle.stream_position()?;
     let padding_needed = (8 - (current_pos % 8)) % 8; // Calculate padding to reach 8-byte boundary
@@ -146,12 +350,57 @@ fn _write_table(
         file_handle.write_all(&[0])?;
     }
     embedded_file.length = length;
-    embedded_file.offset = offset;
     file_handle.write_all(section_marker)?;
     file_handle.flush()?;
     Ok(())
 }
 
+/// Options controlling how a [`Pod5File`] is written.
+#[derive(Debug, Clone, Default)]
+pub struct WriterOptions {
+    /// How inconsistent reads and run information are handled.
+    pub validation: ValidationPolicy,
+    /// How reads pushed with an already pushed `read_id` are handled.
+    pub duplicate_reads: DuplicatePolicy,
+    /// Set each pushed read's `num_samples` from the length of its signal, before it is validated.
+    pub fill_num_samples: bool,
+    /// Also check each pushed run info with `RunInfoData::validate_tags`, e.g. that the
+    /// tracking keys downstream tools rely on are present.
+    pub validate_tags: bool,
+    /// The pod5 specification version to write.
+    pub pod5_version: Pod5Version,
+    /// Extra key/values added to the schema metadata of every table, e.g. pipeline provenance.
+    /// Keys must not use the `MINKNOW:` or `ARROW:` prefixes reserved by the specifications.
+    /// Read them back with `Pod5Reader::custom_metadata`.
+    pub schema_metadata: HashMap<String, String>,
+    /// Extra key/values added to the schema metadata of one table, by its `ContentType`, over those
+    /// of `schema_metadata`. The same prefixes are reserved.
+    pub table_schema_metadata: HashMap<ContentType, HashMap<String, String>>,
+    /// Extra typed columns appended to the reads table after the specification's fields, by name.
+    /// Values are supplied per read in `ReadInfo::extensions`.
+    pub extension_columns: Vec<(String, ExtensionType)>,
+    /// Pore types to add to the `pore_type` dictionary up front, beyond the well known ones.
+    /// Needed for pore types that first appear after reads have been flushed.
+    pub known_pore_types: Vec<PoreType>,
+    /// End reasons to add to the `end_reason` dictionary up front, beyond the pod5 vocabulary.
+    pub known_end_reasons: Vec<EndReason>,
+    /// Arrow IPC buffer compression of the reads and run info tables.
+    pub table_compression: TableCompression,
+    /// How the signal is stored, e.g. `codec::Vbz` as MinKNOW writes it. Uncompressed if `None`.
+    pub signal_codec: Option<Arc<dyn SignalCodec>>,
+}
+
+impl WriterOptions {
+    /// The custom schema metadata of one table, `schema_metadata` with its `table_schema_metadata` over it.
+    fn _custom_metadata(&self, table: ContentType) -> HashMap<String, String> {
+        let mut metadata = self.schema_metadata.clone();
+        if let Some(table_metadata) = self.table_schema_metadata.get(&table) {
+            metadata.extend(table_metadata.clone());
+        }
+        metadata
+    }
+}
+
 /// Represents a Pod5 file, encapsulating all necessary components and metadata for handling Pod5 data.
 pub struct Pod5File {
     /// File handle for reading from or writing to the Pod5 file.
@@ -162,8 +411,8 @@ pub struct Pod5File {
     run_table: EmbeddedFileArgs,
     /// Metadata and positional information for the signal table embedded in the file.
     signal_table: EmbeddedFileArgs,
-    /// Buffer to hold `ReadInfo` data before writing to the file.
-    _reads: Vec<ReadInfo>,
+    /// Buffer to hold `ReadInfo` data, and the signal of each read, before writing to the file.
+    _reads: Vec<(ReadInfo, Int16Array)>,
     /// Buffer to hold signal `RecordBatch` data before writing to the file.
     _signal: Vec<RecordBatch>,
     /// Buffer to hold `RunInfoData` before writing to the file.
@@ -178,6 +427,20 @@ pub struct Pod5File {
     _section_marker: Uuid,
     /// Unique identifier for the file, used for internal tracking and referencing.
     _file_identifier: Uuid,
+    /// Options this file was created with.
+    _options: WriterOptions,
+    /// Every `read_id` pushed so far. Uuids are stored as is, 16 bytes each, so this scales to millions of reads.
+    _read_ids: HashSet<Uuid>,
+    /// Every `read_id` that was pushed more than once, in the order the collisions happened.
+    _duplicate_read_ids: Vec<Uuid>,
+    /// Buffered reads pushed before their run info, so not yet checked against its ADC range.
+    _unchecked_read_ids: HashSet<Uuid>,
+    /// Dictionaries shared by every batch of the reads table.
+    _dictionaries: ReadDictionaries,
+    /// Writer of the reads table, open from the first flush of reads until the table is finished.
+    _reads_writer: Option<FileWriter<File>>,
+    /// How the signal is stored.
+    _signal_codec: Arc<dyn SignalCodec>,
 }
 
 impl Pod5File {
@@ -203,10 +466,49 @@ impl Pod5File {
     /// }
     /// ```
     pub fn new(filepath: &str) -> Result<Self, Box<dyn Error>> {
+        Self::with_options(filepath, WriterOptions::default())
+    }
+
+    /// Creates a new `Pod5File` instance, as [`Pod5File::new`], using the given `WriterOptions`.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let options = WriterOptions {
+    ///     validation: ValidationPolicy::Lenient,
+    /// };
+    /// let pod5_file = Pod5File::with_options("path/to/file.pod5", options)?;
+    /// ```
+    pub fn with_options(filepath: &str, options: WriterOptions) -> Result<Self, Box<dyn Error>> {
+        let file_identifier = Uuid::new_v4();
+        let reads_schema = _with_custom_metadata(
+            _with_extension_columns(
+                create_reads_arrow_schema(&file_identifier, options.pod5_version)?,
+                &options.extension_columns,
+            )?,
+            &options._custom_metadata(ContentType::ReadsTable),
+        )?;
+        let run_schema = _with_custom_metadata(
+            run_info_schema(&file_identifier, options.pod5_version)?,
+            &options._custom_metadata(ContentType::RunInfoTable),
+        )?;
+        let signal_codec = options
+            .signal_codec
+            .clone()
+            .unwrap_or_else(|| Arc::new(Uncompressed));
+        let signal_schema = _with_custom_metadata(
+            signal_schema(&file_identifier, options.pod5_version, &*signal_codec),
+            &options._custom_metadata(ContentType::SignalTable),
+        )?;
+        let mut dictionaries = ReadDictionaries::new();
+        for pore_type in &options.known_pore_types {
+            dictionaries.register_pore_type(pore_type)?;
+        }
+        for end_reason in &options.known_end_reasons {
+            dictionaries.register_end_reason(end_reason)?;
+        }
         let mut file = File::create(filepath)?;
         file.write_all(&SIGNATURE)?;
         let section_marker = Uuid::new_v4();
-        let file_identifier = Uuid::new_v4();
         file.write_all(section_marker.as_bytes())?;
         Ok(Pod5File {
             filehandle: file,
@@ -231,11 +533,18 @@ impl Pod5File {
             _reads: vec![],
             _signal: vec![],
             _run_info: vec![],
-            _reads_schema: Arc::new(create_reads_arrow_schema(&file_identifier)?),
-            _run_schema: Arc::new(run_info_schema(&file_identifier)?),
-            _signal_schema: Arc::new(signal_schema(&file_identifier)),
+            _reads_schema: Arc::new(reads_schema),
+            _run_schema: Arc::new(run_schema),
+            _signal_schema: Arc::new(signal_schema),
             _section_marker: section_marker,
             _file_identifier: file_identifier,
+            _options: options,
+            _read_ids: HashSet::new(),
+            _duplicate_read_ids: vec![],
+            _unchecked_read_ids: HashSet::new(),
+            _dictionaries: dictionaries,
+            _reads_writer: None,
+            _signal_codec: signal_codec,
         })
     }
 
@@ -244,6 +553,13 @@ impl Pod5File {
     /// This method appends the provided `RunInfoData` to the internal run information buffer
     /// of the `Pod5File` struct. It is used to accumulate run information before writing it to the file.
     ///
+    /// The run info is checked with `RunInfoData::validate`, and any problems are handled according
+    /// to the `ValidationPolicy` in the `WriterOptions`.
+    ///
+    /// Pushing a run info identical to one already pushed is a no-op. Pushing a different run info
+    /// with an `acquisition_id` that has already been pushed is a validation problem, and the new
+    /// run info is not added.
+    ///
     /// # Arguments
     /// * `run_info` - The `RunInfoData` instance to be added to the Pod5 file.
     ///
@@ -254,71 +570,311 @@ impl Pod5File {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let mut pod5_file = Pod5File::new("my_file.pod5")?;
     /// let run_info = RunInfoData { /* fields */ };
-    /// pod5_file.push_run_info(run_info);
+    /// pod5_file.push_run_info(run_info)?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn push_run_info(&mut self, run_info: RunInfoData) {
-        self._run_info.push(run_info)
+    pub fn push_run_info(&mut self, run_info: RunInfoData) -> Result<(), Box<dyn Error>> {
+        if let Err(e) = run_info.validate() {
+            self._options.validation.enforce(e.problems)?;
+        }
+        if self._options.validate_tags {
+            if let Err(e) = run_info.validate_tags() {
+                self._options.validation.enforce(e.problems)?;
+            }
+        }
+        match self
+            ._run_info
+            .iter()
+            .find(|x| x.acquisition_id == run_info.acquisition_id)
+        {
+            Some(existing) if *existing == run_info => {
+                debug!(
+                    "Skipping duplicate run info for acquisition {}",
+                    run_info.acquisition_id
+                );
+                Ok(())
+            }
+            Some(_) => Ok(self._options.validation.enforce(vec![format!(
+                "run info for acquisition {} was pushed twice with different values, keeping the first",
+                run_info.acquisition_id
+            )])?),
+            None => {
+                // Once frozen, reads of this run are refused with a clear error when pushed
+                if !self._dictionaries.is_frozen() {
+                    self._dictionaries
+                        .register_run_info(&run_info.acquisition_id)?;
+                }
+                self._run_info.push(run_info);
+                Ok(())
+            }
+        }
     }
 
     /// Dump all created Run info RecordBatches (tables) into the file, and set the offset and length correctly
     /// on the Embedded file args
-    pub fn write_run_info_to_ipc(&mut self) {
-        let batches = create_run_info_batch(self._run_schema.clone(), &self._run_info).unwrap();
+    ///
+    /// Refused while the reads table is open, i.e. between `flush_reads` and `write_reads_to_ipc`.
+    pub fn write_run_info_to_ipc(&mut self) -> Result<(), Box<dyn Error>> {
+        self._check_reads_table_closed("run info table")?;
+        let batches = create_run_info_batch(self._run_schema.clone(), &self._run_info)?;
         _write_table(
             &mut self.filehandle,
             self._section_marker.as_bytes(),
+            &self._run_schema,
             &batches,
             &mut self.run_table,
+            self._options.table_compression,
         )
-        .unwrap();
+    }
+
+    /// Refuse to write another table, or the footer, while the reads table is open, as it would land inside it.
+    fn _check_reads_table_closed(&self, table: &str) -> Result<(), Box<dyn Error>> {
+        if self._reads_writer.is_some() {
+            return Err(format!(
+                "cannot write the {table} while the reads table is open, finish it with write_reads_to_ipc first"
+            )
+            .into());
+        }
+        Ok(())
     }
 
     /// Push reads to internal buffer, ready to be written out
     /// By a call to write_reads_to_ipc
-    pub fn push_read(&mut self, read: ReadInfo) {
-        self._reads.push(read)
+    ///
+    /// Each read is checked with `ReadInfo::validate`, its extension values with `ReadInfo::check_extensions`,
+    /// and against its run info with `ReadInfo::validate_against`, when pushed if that has already been
+    /// pushed, otherwise when the read is flushed.
+    /// Any problems are handled according to the `ValidationPolicy` in the `WriterOptions`.
+    ///
+    /// A read whose `read_id` has already been pushed is handled according to the
+    /// `DuplicatePolicy` in the `WriterOptions`, and is recorded in `duplicate_read_ids`.
+    pub fn push_read(&mut self, mut read: ReadInfo) -> Result<(), Box<dyn Error>> {
+        let signal = std::mem::take(&mut read.signal_);
+        self._check_read(&mut read, &signal)?;
+        // Hands the signal over to Arrow without copying it
+        self._buffer_read(read, Int16Array::from(signal))
+    }
+
+    /// Push a read whose signal is borrowed, as `push_read` does.
+    ///
+    /// The signal is copied once, into the Arrow buffer it is written from. A read that also has a
+    /// `signal_` of its own is refused, rather than one of the two signals being dropped.
+    pub fn push_read_ref(&mut self, read: ReadInfoRef<'_>) -> Result<(), Box<dyn Error>> {
+        if !read.read.signal_.is_empty() {
+            return Err(Box::new(ValidationError {
+                problems: vec![format!(
+                    "read {} has its own signal_, which must be empty when its signal is borrowed",
+                    read.read.read_id
+                )],
+            }));
+        }
+        let mut metadata = read.read.without_signal();
+        self._check_read(&mut metadata, read.signal)?;
+        self._buffer_read(metadata, Int16Array::from(read.signal.to_vec()))
+    }
+
+    /// Validate a read, without its signal, and the signal it will be written with.
+    fn _check_read(&self, read: &mut ReadInfo, signal: &[i16]) -> Result<(), Box<dyn Error>> {
+        if self._options.fill_num_samples {
+            read.num_samples = signal.len() as u64;
+        }
+        self._dictionaries.check_read(read)?;
+        let read_ref = ReadInfoRef::new(read, signal);
+        if let Err(e) = read_ref.validate() {
+            self._options.validation.enforce(e.problems)?;
+        }
+        let (names, problems): (Vec<String>, Vec<String>) = read
+            .check_extensions(&self._reads_schema)
+            .into_iter()
+            .unzip();
+        self._options.validation.enforce(problems)?;
+        if let Some(run_info) = self
+            ._run_info
+            .iter()
+            .find(|x| x.acquisition_id == read.run_info)
+        {
+            if let Err(e) = read_ref.validate_against(run_info) {
+                self._options.validation.enforce(e.problems)?;
+            }
+        }
+        // Leniently dropped, as they cannot be written
+        for name in names {
+            read.extensions.remove(&name);
+        }
+        Ok(())
+    }
+
+    /// Buffer a validated read, handling a duplicate `read_id`.
+    fn _buffer_read(&mut self, read: ReadInfo, signal: Int16Array) -> Result<(), Box<dyn Error>> {
+        let unchecked = !self
+            ._run_info
+            .iter()
+            .any(|x| x.acquisition_id == read.run_info);
+        if self._read_ids.insert(read.read_id) {
+            if unchecked {
+                self._unchecked_read_ids.insert(read.read_id);
+            }
+            self._reads.push((read, signal));
+            return Ok(());
+        }
+        self._duplicate_read_ids.push(read.read_id);
+        match self._options.duplicate_reads {
+            DuplicatePolicy::Reject => Err(Box::new(ValidationError {
+                problems: vec![format!("read {} has already been pushed", read.read_id)],
+            })),
+            DuplicatePolicy::Overwrite => {
+                let index = self
+                    ._reads
+                    .iter()
+                    .position(|x| x.0.read_id == read.read_id)
+                    .ok_or_else(|| ValidationError {
+                        problems: vec![format!(
+                            "read {} has already been flushed, so cannot be overwritten",
+                            read.read_id
+                        )],
+                    })?;
+                if unchecked {
+                    self._unchecked_read_ids.insert(read.read_id);
+                } else {
+                    self._unchecked_read_ids.remove(&read.read_id);
+                }
+                self._reads[index] = (read, signal);
+                Ok(())
+            }
+            DuplicatePolicy::Skip => {
+                debug!("Skipping duplicate read {}", read.read_id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Every `read_id` that was pushed more than once, once per collision.
+    pub fn duplicate_read_ids(&self) -> &[Uuid] {
+        &self._duplicate_read_ids
     }
 
-    pub fn write_signal_to_ipc(&mut self) {
+    /// Write the signal of every read into the file.
+    ///
+    /// Refused while the reads table is open, i.e. between `flush_reads` and `write_reads_to_ipc`.
+    pub fn write_signal_to_ipc(&mut self) -> Result<(), Box<dyn Error>> {
+        self._check_reads_table_closed("signal table")?;
         _write_table(
             &mut self.filehandle,
             self._section_marker.as_bytes(),
+            &self._signal_schema,
             &self._signal,
             &mut self.signal_table,
+            // Signal compression is the signal table's own concern
+            TableCompression::None,
         )
-        .unwrap();
     }
 
-    /// Write the reads and signal in the internal buffer into the file. DO NOT CALL MORE THAN ONCE YOU WILL MESS THINGS UP
-    pub fn write_reads_to_ipc(&mut self) {
+    /// Check that every pushed read refers to a pushed run info, by `acquisition_id`, and that reads
+    /// pushed before their run info are within its ADC range.
+    fn _check_run_info_references(&self) -> Result<(), Box<dyn Error>> {
+        let run_infos: HashMap<&str, &RunInfoData> = self
+            ._run_info
+            .iter()
+            .map(|x| (x.acquisition_id.as_str(), x))
+            .collect();
+        let mut problems = vec![];
+        let mut reported = HashSet::new();
+        for (read, signal) in &self._reads {
+            match run_infos.get(read.run_info.as_str()) {
+                Some(run_info) if self._unchecked_read_ids.contains(&read.read_id) => {
+                    if let Err(e) =
+                        ReadInfoRef::new(read, signal.values()).validate_against(run_info)
+                    {
+                        problems.extend(e.problems);
+                    }
+                }
+                Some(_) => {}
+                None if reported.insert(read.run_info.as_str()) => {
+                    problems.push(format!(
+                        "read {} refers to run info {}, which has not been pushed",
+                        read.read_id, read.run_info
+                    ));
+                }
+                None => {}
+            }
+        }
+        Ok(self._options.validation.enforce(problems)?)
+    }
+
+    /// Write the buffered reads into the file, as the next batch of the reads table, and clear the buffer.
+    ///
+    /// Reads can be flushed as they are pushed, to bound memory, until the table is finished
+    /// by `write_reads_to_ipc`. The signal of flushed reads is still buffered until `write_signal_to_ipc`.
+    /// Writing the other tables, or the footer, in between is refused with an error.
+    ///
+    /// The dictionary columns are fixed by the first flush that writes any reads, so reads pushed after
+    /// that must use pore types, end reasons and run infos that were already known. See `ReadDictionaries`.
+    pub fn flush_reads(&mut self) -> Result<(), Box<dyn Error>> {
+        self._check_run_info_references()?;
         let batches = create_read_batches(
             self._reads_schema.clone(),
             &self._reads,
+            &mut self._dictionaries,
             &mut self._signal,
             self._signal_schema.clone(),
-        )
-        .unwrap();
-        _write_table(
+            &*self._signal_codec,
+        )?;
+        let writer = match &mut self._reads_writer {
+            Some(writer) => writer,
+            None => {
+                self.read_table.offset = self.filehandle.stream_position()? as i64;
+                // A duplicate handle shares the file position, so the table lands where it was started
+                self._reads_writer.insert(FileWriter::try_new_with_options(
+                    self.filehandle.try_clone()?,
+                    &self._reads_schema,
+                    self._options.table_compression.ipc_write_options()?,
+                )?)
+            }
+        };
+        if !batches.is_empty() {
+            // One batch per flush, as compression of one row batches costs more than it saves
+            writer.write(&concat_batches(&self._reads_schema, &batches)?)?;
+            self._dictionaries.freeze();
+        }
+        self._reads.clear();
+        self._unchecked_read_ids.clear();
+        Ok(())
+    }
+
+    /// Flush any buffered reads, and finish the reads table. DO NOT CALL MORE THAN ONCE YOU WILL MESS THINGS UP
+    ///
+    /// The run info of every read is checked against the pushed run infos first,
+    /// so run infos must be pushed before this is called.
+    pub fn write_reads_to_ipc(&mut self) -> Result<(), Box<dyn Error>> {
+        self.flush_reads()?;
+        let mut writer = self
+            ._reads_writer
+            .take()
+            .expect("flush_reads opens the reads writer");
+        writer.finish()?;
+        writer.into_inner()?;
+        _finish_table(
             &mut self.filehandle,
             self._section_marker.as_bytes(),
-            &batches,
             &mut self.read_table,
-        )
-        .unwrap();
+        )?;
+        Ok(())
     }
 
     /// Write the footer and finish the file. Again please ONLY CALL ONCE PER FILE
-    pub fn write_footer(&mut self) {
+    ///
+    /// Refused while the reads table is open, i.e. between `flush_reads` and `write_reads_to_ipc`.
+    pub fn write_footer(&mut self) -> Result<(), Box<dyn Error>> {
+        self._check_reads_table_closed("footer")?;
         let embedded_args = vec![&self.read_table, &self.run_table, &self.signal_table];
         write_flatbuffer_footer(
             &mut self.filehandle,
             embedded_args,
             self._file_identifier,
             self._section_marker.as_bytes(),
+            self._options.pod5_version,
         )
-        .unwrap()
     }
 }
 
@@ -328,30 +884,34 @@ mod tests {
     use arrow::ipc::reader::FileReader;
     use log::info;
 
-    use crate::reads::dummy_read_row;
+    use crate::reads::{
+        decode_dictionary_column, dummy_read_row, EndReason, ExtensionValue, PoreType,
+    };
     use run_info::dummy_run_info;
     use std::io::{Read, Seek, SeekFrom};
 
     use crate::footer::read_pod5_footer;
+    use crate::reader::Pod5Reader;
+    use crate::reads::ReadInfoRef;
 
     use super::*;
 
     fn test() -> arrow::error::Result<()> {
         let mut pod5 = Pod5File::new("test_builder.pod5").unwrap();
 
-        pod5.push_run_info(dummy_run_info());
-        pod5.write_run_info_to_ipc();
+        pod5.push_run_info(dummy_run_info()).unwrap();
+        pod5.write_run_info_to_ipc().unwrap();
         println!("{:#?}", pod5.run_table.length);
         info!("helo");
         let read = dummy_read_row(None).unwrap();
         let read_2 = dummy_read_row(Some("9e81bb6a-8610-4907-b4dd-4ed834fc414d")).unwrap();
 
-        pod5.push_read(read);
-        pod5.push_read(read_2);
-        pod5.write_reads_to_ipc();
+        pod5.push_read(read).unwrap();
+        pod5.push_read(read_2).unwrap();
+        pod5.write_reads_to_ipc().unwrap();
         // println!("{:#?}", pod5._signal);
-        pod5.write_signal_to_ipc();
-        pod5.write_footer();
+        pod5.write_signal_to_ipc().unwrap();
+        pod5.write_footer().unwrap();
 
         Ok(())
     }
@@ -382,10 +942,958 @@ mod tests {
         Ok(batches)
     }
 
+    /// Write `reads` of `run_info` to `path` with `options`, and open the file for reading.
+    ///
+    /// The writer is returned too, for the lengths of the tables it wrote.
+    fn write_reads(
+        path: &str,
+        options: WriterOptions,
+        run_info: RunInfoData,
+        reads: &[ReadInfo],
+    ) -> (Pod5File, Pod5Reader) {
+        let mut pod5 = Pod5File::with_options(path, options).unwrap();
+        pod5.push_run_info(run_info).unwrap();
+        pod5.write_run_info_to_ipc().unwrap();
+        for read in reads {
+            pod5.push_read(read.clone()).unwrap();
+        }
+        pod5.write_reads_to_ipc().unwrap();
+        pod5.write_signal_to_ipc().unwrap();
+        pod5.write_footer().unwrap();
+        (pod5, Pod5Reader::open(path).unwrap())
+    }
+
     #[test]
     fn does_it_work() {
         test().unwrap()
     }
+
+    #[test]
+    fn test_run_info_references() {
+        let path = std::env::temp_dir().join("podders_run_info_references.pod5");
+        let mut pod5 = Pod5File::new(path.to_str().unwrap()).unwrap();
+        pod5.push_run_info(dummy_run_info()).unwrap();
+        // Identical run infos are deduplicated
+        pod5.push_run_info(dummy_run_info()).unwrap();
+        assert_eq!(pod5._run_info.len(), 1);
+        // Conflicting run infos are refused
+        let mut conflicting = dummy_run_info();
+        conflicting.sample_rate = 4000;
+        assert!(pod5.push_run_info(conflicting).is_err());
+
+        let mut read = dummy_read_row(None).unwrap();
+        read.run_info = "not_a_run".to_string();
+        pod5.push_read(read).unwrap();
+        let err = pod5.write_reads_to_ipc().unwrap_err();
+        assert!(err.to_string().contains("not_a_run"));
+
+        let path = std::env::temp_dir().join("podders_run_info_references_lenient.pod5");
+        let options = WriterOptions {
+            validation: ValidationPolicy::Lenient,
+            ..Default::default()
+        };
+        let mut pod5 = Pod5File::with_options(path.to_str().unwrap(), options).unwrap();
+        let mut read = dummy_read_row(None).unwrap();
+        read.run_info = "not_a_run".to_string();
+        pod5.push_read(read).unwrap();
+        pod5.write_reads_to_ipc().unwrap();
+    }
+
+    #[test]
+    fn test_duplicate_reads() {
+        let path = std::env::temp_dir().join("podders_duplicate_reads.pod5");
+        let mut pod5 = Pod5File::new(path.to_str().unwrap()).unwrap();
+        pod5.push_read(dummy_read_row(None).unwrap()).unwrap();
+        assert!(pod5.push_read(dummy_read_row(None).unwrap()).is_err());
+        assert_eq!(pod5._reads.len(), 1);
+
+        for (policy, channel) in [(DuplicatePolicy::Overwrite, 2), (DuplicatePolicy::Skip, 1)] {
+            let options = WriterOptions {
+                duplicate_reads: policy,
+                ..Default::default()
+            };
+            let mut pod5 = Pod5File::with_options(path.to_str().unwrap(), options).unwrap();
+            pod5.push_read(dummy_read_row(None).unwrap()).unwrap();
+            let mut read = dummy_read_row(None).unwrap();
+            read.channel = 2;
+            pod5.push_read(read).unwrap();
+            assert_eq!(pod5._reads.len(), 1);
+            assert_eq!(pod5._reads[0].0.channel, channel);
+            assert_eq!(pod5.duplicate_read_ids(), &[pod5._reads[0].0.read_id]);
+        }
+    }
+
+    #[test]
+    fn test_read_pushed_before_run_info() {
+        let path = std::env::temp_dir().join("podders_read_pushed_before_run_info.pod5");
+        let mut pod5 = Pod5File::new(path.to_str().unwrap()).unwrap();
+        let mut read = dummy_read_row(None).unwrap();
+        read.signal_[0] = 30000;
+        pod5.push_read(read).unwrap();
+        let mut run_info = dummy_run_info();
+        (run_info.adc_min, run_info.adc_max) = (-4096, 4095);
+        pod5.push_run_info(run_info).unwrap();
+        let err = pod5.write_reads_to_ipc().unwrap_err();
+        assert!(err.to_string().contains("outside the adc range"));
+    }
+
+    #[test]
+    fn test_read_validation() {
+        let path = std::env::temp_dir().join("podders_read_validation.pod5");
+        let mut pod5 = Pod5File::new(path.to_str().unwrap()).unwrap();
+        let mut read = dummy_read_row(None).unwrap();
+        read.num_samples = 0;
+        read.well = 0;
+        let err = pod5.push_read(read).unwrap_err();
+        let err = err.downcast_ref::<ValidationError>().unwrap();
+        assert_eq!(err.problems.len(), 2);
+        assert!(pod5._reads.is_empty());
+        // Written as R10.4.1, so would read back as PoreType::R10
+        let mut read = dummy_read_row(None).unwrap();
+        read.pore_type = PoreType::Other("R10.4.1".to_string());
+        assert!(pod5.push_read(read).is_err());
+        let mut read = dummy_read_row(None).unwrap();
+        read.end_reason = EndReason::Other("signal_positive".to_string());
+        assert!(pod5.push_read(read).is_err());
+
+        let options = WriterOptions {
+            fill_num_samples: true,
+            ..Default::default()
+        };
+        let mut pod5 = Pod5File::with_options(path.to_str().unwrap(), options).unwrap();
+        let mut read = dummy_read_row(None).unwrap();
+        read.num_samples = 0;
+        pod5.push_read(read).unwrap();
+        assert_eq!(pod5._reads[0].0.num_samples, pod5._reads[0].1.len() as u64);
+    }
+
+    #[test]
+    fn test_run_info_tags() {
+        use run_info::{ContextTag, TrackingKey};
+
+        let mut run_info = RunInfoData::builder("acquisition_1")
+            .context_tag(ContextTag::ExperimentType, "genomic_dna")
+            .context_tag("sample_frequency", "5000")
+            .tracking_id(TrackingKey::RunId, "run_1")
+            .build()
+            .unwrap();
+        assert_eq!(run_info.experiment_type(), Some("genomic_dna"));
+        assert_eq!(run_info.sample_frequency(), Some(5000));
+        assert_eq!(run_info.run_id(), Some("run_1"));
+        run_info.set_barcoding_enabled(true);
+        assert_eq!(run_info.context_tags["barcoding_enabled"], "1");
+        assert_eq!(run_info.barcoding_enabled(), Some(true));
+        run_info.set_context_tag(ContextTag::LocalBasecalling, "maybe");
+        assert_eq!(run_info.local_basecalling(), None);
+
+        let path = std::env::temp_dir().join("podders_run_info_tags.pod5");
+        let options = WriterOptions {
+            validate_tags: true,
+            ..Default::default()
+        };
+        let mut pod5 = Pod5File::with_options(path.to_str().unwrap(), options).unwrap();
+        let err = pod5.push_run_info(run_info.clone()).unwrap_err();
+        let err = err.downcast_ref::<ValidationError>().unwrap();
+        // Four missing tracking keys, and the local_basecalling flag
+        assert_eq!(err.problems.len(), 5);
+        for key in TrackingKey::REQUIRED {
+            run_info.set_tracking(key, "set");
+        }
+        run_info.set_local_basecalling(false);
+        pod5.push_run_info(run_info).unwrap();
+    }
+
+    #[test]
+    fn test_run_info_timestamps() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let run_info = dummy_run_info();
+        let acquisition_start = run_info.acquisition_start();
+        assert_eq!(
+            acquisition_start.duration_since(UNIX_EPOCH).unwrap(),
+            Duration::from_millis(run_info.acquisition_start_time as u64)
+        );
+        let protocol_start = acquisition_start - Duration::from_millis(1500);
+        let mut run_info = RunInfoData::builder("acquisition_1")
+            .start_system_times(acquisition_start, protocol_start)
+            .build()
+            .unwrap();
+        assert_eq!(run_info.acquisition_start(), acquisition_start);
+        assert_eq!(
+            run_info.protocol_start_time,
+            run_info.acquisition_start_time - 1500
+        );
+        assert_eq!(run_info.protocol_start(), protocol_start);
+        run_info.protocol_start_time = -1500;
+        assert_eq!(
+            run_info.protocol_start(),
+            UNIX_EPOCH - Duration::from_millis(1500)
+        );
+
+        let mut read = dummy_read_row(None).unwrap();
+        read.start = u64::from(run_info.sample_rate) * 3 + 1;
+        assert_eq!(
+            read.start_time(&run_info),
+            Some(acquisition_start + Duration::from_secs(3) + Duration::from_micros(200))
+        );
+        read.start = u64::MAX;
+        assert!(read.start_time(&run_info).is_some());
+        run_info.sample_rate = 0;
+        assert_eq!(read.start_time(&run_info), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_run_info_timestamps_chrono() {
+        use chrono::{TimeZone, Utc};
+
+        let acquisition_start = Utc.with_ymd_and_hms(2023, 12, 20, 9, 30, 0).unwrap();
+        let run_info = RunInfoData::builder("acquisition_1")
+            .start_system_times(acquisition_start, acquisition_start)
+            .build()
+            .unwrap();
+        assert_eq!(run_info.acquisition_start_time, 1703064600000);
+        assert_eq!(run_info.acquisition_start_utc(), acquisition_start);
+        assert_eq!(run_info.protocol_start_utc(), acquisition_start);
+        let mut read = dummy_read_row(None).unwrap();
+        read.start = u64::from(run_info.sample_rate) * 60;
+        assert_eq!(
+            read.start_time_utc(&run_info),
+            Some(acquisition_start + chrono::Duration::minutes(1))
+        );
+    }
+
+    #[test]
+    fn test_run_info_validation() {
+        let mut run_info = dummy_run_info();
+        assert!(run_info.validate().is_ok());
+        run_info.adc_min = run_info.adc_max;
+        run_info.sample_rate = 0;
+        run_info.protocol_start_time = run_info.acquisition_start_time + 1;
+        assert_eq!(run_info.validate().unwrap_err().problems.len(), 3);
+
+        let path = std::env::temp_dir().join("podders_run_info_validation.pod5");
+        let mut pod5 = Pod5File::new(path.to_str().unwrap()).unwrap();
+        assert!(pod5.push_run_info(run_info).is_err());
+
+        let mut run_info = dummy_run_info();
+        run_info.adc_min = -10;
+        run_info.adc_max = 10;
+        pod5.push_run_info(run_info).unwrap();
+        let err = pod5.push_read(dummy_read_row(None).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("outside the adc range"));
+    }
+
+    #[test]
+    fn test_dictionary_round_trip() {
+        let path = std::env::temp_dir().join("podders_pore_type.pod5");
+        let path = path.to_str().unwrap();
+        let mut read = dummy_read_row(None).unwrap();
+        read.pore_type = PoreType::Other("RNA004".to_string());
+        read.end_reason = EndReason::API_REQUEST;
+        let other = dummy_read_row(Some("9e81bb6a-8610-4907-b4dd-4ed834fc414d")).unwrap();
+        write_reads(path, Default::default(), dummy_run_info(), &[read, other]);
+
+        let file_info = read_pod5_footer(path, ContentType::ReadsTable);
+        let batches = read_arrow_table(path, file_info.offset, file_info.length).unwrap();
+        let pores: Vec<PoreType> = batches
+            .iter()
+            .flat_map(|batch| {
+                decode_dictionary_column::<PoreType>(batch.column_by_name("pore_type").unwrap())
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            pores,
+            vec![PoreType::Other("RNA004".to_string()), PoreType::R10]
+        );
+        let end_reasons: Vec<EndReason> = batches
+            .iter()
+            .flat_map(|batch| {
+                decode_dictionary_column::<EndReason>(batch.column_by_name("end_reason").unwrap())
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            end_reasons,
+            vec![EndReason::API_REQUEST, EndReason::SIGNAL_POSITIVE]
+        );
+    }
+
+    #[test]
+    fn test_writing_pod5_version() {
+        let path = std::env::temp_dir().join("podders_pod5_version.pod5");
+        let path = path.to_str().unwrap();
+        let options = WriterOptions {
+            pod5_version: Pod5Version::V0_3_10,
+            ..Default::default()
+        };
+        let mut read = dummy_read_row(None).unwrap();
+        read.open_pore_level = Some(220.5);
+        write_reads(path, options, dummy_run_info(), &[read]);
+
+        let file_info = read_pod5_footer(path, ContentType::ReadsTable);
+        let batches = read_arrow_table(path, file_info.offset, file_info.length).unwrap();
+        let schema = batches[0].schema();
+        assert_eq!(schema.fields().len(), 22);
+        assert_eq!(schema.metadata()["MINKNOW:pod5_version"], "0.3.10");
+        let open_pore_level = batches[0].column_by_name("open_pore_level").unwrap();
+        let open_pore_level = open_pore_level
+            .as_any()
+            .downcast_ref::<arrow::array::Float32Array>()
+            .unwrap();
+        assert_eq!(open_pore_level.value(0), 220.5);
+
+        let mut file = File::open(path).unwrap();
+        file.seek(SeekFrom::End(-32)).unwrap();
+        let mut buffer = [0; 8];
+        file.read_exact(&mut buffer).unwrap();
+        let footer_length = i64::from_le_bytes(buffer);
+        file.seek(SeekFrom::Current(-(8 + footer_length))).unwrap();
+        let mut buf = vec![0; footer_length as usize];
+        file.read_exact(&mut buf).unwrap();
+        let footer = root_as_footer(&buf).unwrap();
+        assert_eq!(footer.pod5_version(), Some("0.3.10"));
+    }
+
+    #[test]
+    fn test_custom_schema_metadata() {
+        let path = std::env::temp_dir().join("podders_custom_metadata.pod5");
+        let path = path.to_str().unwrap();
+        let schema_metadata: HashMap<String, String> =
+            [("pipeline_version", "1.2.3"), ("operator", "rory")]
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+        let options = WriterOptions {
+            schema_metadata: schema_metadata.clone(),
+            ..Default::default()
+        };
+        let mut pod5 = Pod5File::with_options(path, options).unwrap();
+        pod5.write_run_info_to_ipc().unwrap();
+        pod5.write_reads_to_ipc().unwrap();
+        pod5.write_signal_to_ipc().unwrap();
+        pod5.write_footer().unwrap();
+
+        let mut reader = Pod5Reader::open(path).unwrap();
+        for table in [
+            ContentType::RunInfoTable,
+            ContentType::ReadsTable,
+            ContentType::SignalTable,
+        ] {
+            assert_eq!(reader.custom_metadata(table).unwrap(), schema_metadata);
+            let schema = reader.table_schema(table).unwrap();
+            assert_eq!(schema.metadata()["MINKNOW:pod5_version"], "0.3.2");
+        }
+
+        let options = WriterOptions {
+            schema_metadata: [("MINKNOW:software".to_string(), "me".to_string())].into(),
+            ..Default::default()
+        };
+        assert!(Pod5File::with_options(path, options).is_err());
+    }
+
+    #[test]
+    fn test_extension_columns() {
+        let path = std::env::temp_dir().join("podders_extension_columns.pod5");
+        let path = path.to_str().unwrap();
+        let options = WriterOptions {
+            extension_columns: vec![
+                ("adaptive_sampling".to_string(), ExtensionType::Utf8),
+                ("parent_read_id".to_string(), ExtensionType::Uuid),
+            ],
+            ..Default::default()
+        };
+        let mut pod5 = Pod5File::with_options(path, options).unwrap();
+        pod5.push_run_info(dummy_run_info()).unwrap();
+        pod5.write_run_info_to_ipc().unwrap();
+        let mut read = dummy_read_row(None).unwrap();
+        read.extensions.insert(
+            "adaptive_sampling".to_string(),
+            ExtensionValue::Utf8("stop_receiving".to_string()),
+        );
+        read.extensions.insert(
+            "parent_read_id".to_string(),
+            ExtensionValue::Uuid(Uuid::new_v4()),
+        );
+        let mut bad_read = dummy_read_row(Some("9e81bb6a-8610-4907-b4dd-4ed834fc414d")).unwrap();
+        bad_read
+            .extensions
+            .insert("parent_read_id".to_string(), ExtensionValue::UInt32(1));
+        assert!(pod5.push_read(bad_read.clone()).is_err());
+        bad_read.extensions.clear();
+        pod5.push_read(read.clone()).unwrap();
+        pod5.push_read(bad_read.clone()).unwrap();
+        pod5.write_reads_to_ipc().unwrap();
+        pod5.write_signal_to_ipc().unwrap();
+        pod5.write_footer().unwrap();
+
+        let mut reader = Pod5Reader::open(path).unwrap();
+        let reads = reader.reads().unwrap();
+        assert_eq!(reads, vec![read, bad_read]);
+    }
+
+    #[test]
+    fn test_push_read_ref() {
+        let path = std::env::temp_dir().join("podders_push_read_ref.pod5");
+        let path = path.to_str().unwrap();
+        let mut pod5 = Pod5File::new(path).unwrap();
+        pod5.push_run_info(dummy_run_info()).unwrap();
+        pod5.write_run_info_to_ipc().unwrap();
+        let read = dummy_read_row(None).unwrap();
+        let metadata = read.without_signal();
+        assert!(metadata.signal_.is_empty());
+        assert!(pod5
+            .push_read_ref(ReadInfoRef::new(&read, &read.signal_))
+            .is_err());
+        assert!(pod5
+            .push_read_ref(ReadInfoRef::new(&metadata, &read.signal_[1..]))
+            .is_err());
+        pod5.push_read_ref(ReadInfoRef::new(&metadata, &read.signal_))
+            .unwrap();
+        pod5.write_reads_to_ipc().unwrap();
+        pod5.write_signal_to_ipc().unwrap();
+        pod5.write_footer().unwrap();
+
+        let mut reader = Pod5Reader::open(path).unwrap();
+        assert_eq!(reader.reads().unwrap(), vec![read]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let path = std::env::temp_dir().join("podders_serde.pod5");
+        let path = path.to_str().unwrap();
+        let run_info = dummy_run_info();
+        let json = serde_json::to_string(&run_info).unwrap();
+        assert_eq!(
+            serde_json::from_str::<RunInfoData>(&json).unwrap(),
+            run_info
+        );
+
+        let mut read = dummy_read_row(None).unwrap();
+        read.median_before = f32::NAN;
+        read.pore_type = PoreType::Other("RNA004".to_string());
+        let json = serde_json::to_value(&read).unwrap();
+        assert_eq!(json["read_id"], read.read_id.to_string());
+        assert_eq!(json["pore_type"], "RNA004");
+        assert_eq!(json["end_reason"], read.end_reason.to_string());
+        assert!(json["median_before"].is_null());
+        let round_trip: ReadInfo = serde_json::from_value(json).unwrap();
+        assert!(round_trip.median_before.is_nan());
+        assert_eq!(round_trip.signal_, read.signal_);
+
+        let parent = ExtensionValue::Uuid(read.read_id);
+        let json = serde_json::to_value(&parent).unwrap();
+        assert_eq!(json["Uuid"], read.read_id.to_string());
+        assert_eq!(
+            serde_json::from_value::<ExtensionValue>(json).unwrap(),
+            parent
+        );
+
+        // Metadata only, replayed into a file with the signal kept elsewhere
+        let json = serde_json::to_value(read.without_signal()).unwrap();
+        assert!(json.get("signal_").is_none());
+        let metadata: ReadInfo = serde_json::from_value(json).unwrap();
+        let mut pod5 = Pod5File::new(path).unwrap();
+        pod5.push_run_info(run_info).unwrap();
+        pod5.write_run_info_to_ipc().unwrap();
+        pod5.push_read_ref(ReadInfoRef::new(&metadata, &read.signal_))
+            .unwrap();
+        pod5.write_reads_to_ipc().unwrap();
+        pod5.write_signal_to_ipc().unwrap();
+        pod5.write_footer().unwrap();
+        let reads = Pod5Reader::open(path).unwrap().reads().unwrap();
+        assert_eq!(reads[0].read_id, read.read_id);
+        assert_eq!(reads[0].signal_, read.signal_);
+    }
+
+    #[test]
+    fn test_streamed_reads() {
+        use crate::dictionary::DictionaryError;
+
+        let path = std::env::temp_dir().join("podders_streamed_reads.pod5");
+        let path = path.to_str().unwrap();
+        let rna = PoreType::Other("RNA004".to_string());
+        let options = WriterOptions {
+            known_pore_types: vec![rna.clone()],
+            ..Default::default()
+        };
+        let mut pod5 = Pod5File::with_options(path, options).unwrap();
+        pod5.push_run_info(dummy_run_info()).unwrap();
+        pod5.write_run_info_to_ipc().unwrap();
+        let first = dummy_read_row(None).unwrap();
+        pod5.push_read(first.clone()).unwrap();
+        pod5.flush_reads().unwrap();
+
+        // Values first seen after the flush must already be in the dictionaries
+        let mut late = dummy_read_row(Some("9e81bb6a-8610-4907-b4dd-4ed834fc414d")).unwrap();
+        late.pore_type = rna;
+        late.end_reason = EndReason::PAUSED;
+        let mut unknown = late.clone();
+        unknown.read_id = Uuid::new_v4();
+        unknown.pore_type = PoreType::Other("R11".to_string());
+        let err = pod5.push_read(unknown).unwrap_err();
+        let err = err.downcast_ref::<DictionaryError>().unwrap();
+        assert_eq!((err.column, err.value.as_str()), ("pore_type", "R11"));
+        pod5.push_read(late.clone()).unwrap();
+        pod5.write_reads_to_ipc().unwrap();
+        pod5.write_signal_to_ipc().unwrap();
+        pod5.write_footer().unwrap();
+
+        let mut reader = Pod5Reader::open(path).unwrap();
+        assert_eq!(reader.reads().unwrap(), vec![first, late]);
+    }
+
+    #[test]
+    fn test_tables_written_while_reads_open() {
+        let path = std::env::temp_dir().join("podders_reads_open.pod5");
+        let path = path.to_str().unwrap();
+        let mut pod5 = Pod5File::new(path).unwrap();
+        pod5.push_run_info(dummy_run_info()).unwrap();
+        let read = dummy_read_row(None).unwrap();
+        pod5.push_read(read.clone()).unwrap();
+        pod5.flush_reads().unwrap();
+        // Any of these would land inside the reads table
+        assert!(pod5.write_run_info_to_ipc().is_err());
+        assert!(pod5.write_signal_to_ipc().is_err());
+        assert!(pod5.write_footer().is_err());
+        pod5.write_reads_to_ipc().unwrap();
+        pod5.write_run_info_to_ipc().unwrap();
+        pod5.write_signal_to_ipc().unwrap();
+        pod5.write_footer().unwrap();
+
+        let mut reader = Pod5Reader::open(path).unwrap();
+        assert_eq!(reader.reads().unwrap(), vec![read]);
+        assert_eq!(reader.run_infos().unwrap(), vec![dummy_run_info()]);
+    }
+
+    #[test]
+    fn test_table_compression() {
+        let mut reads_table_lengths = vec![];
+        for compression in [
+            TableCompression::None,
+            TableCompression::Lz4Frame,
+            TableCompression::Zstd,
+        ] {
+            let path = std::env::temp_dir().join(format!("podders_{compression:?}.pod5"));
+            let path = path.to_str().unwrap();
+            let options = WriterOptions {
+                table_compression: compression,
+                ..Default::default()
+            };
+            let reads: Vec<ReadInfo> = (0..50)
+                .map(|_| {
+                    let mut read = dummy_read_row(None).unwrap();
+                    read.read_id = Uuid::new_v4();
+                    read.signal_.truncate(100);
+                    read.num_samples = 100;
+                    read
+                })
+                .collect();
+            let (pod5, mut reader) = write_reads(path, options, dummy_run_info(), &reads);
+            reads_table_lengths.push(pod5.read_table.length);
+            assert_eq!(reader.reads().unwrap(), reads);
+            let run_infos = reader.read_table(ContentType::RunInfoTable).unwrap();
+            assert_eq!(run_infos[0].num_rows(), 1);
+        }
+        assert!(reads_table_lengths[1] < reads_table_lengths[0]);
+        assert!(reads_table_lengths[2] < reads_table_lengths[0]);
+    }
+
+    #[test]
+    fn test_svb16() {
+        use crate::codec::{svb16_decode, svb16_encode};
+
+        // Zigzag deltas 0, 2, 3, 602, only the last of which needs two bytes
+        let packed = svb16_encode(&[0, 1, -1, 300]);
+        assert_eq!(packed, vec![0b0000_1000, 0x00, 0x02, 0x03, 0x5a, 0x02]);
+        let samples = vec![i16::MIN, i16::MAX, 0, -1, 1, 255, -256, 12, 12, i16::MIN];
+        let packed = svb16_encode(&samples);
+        assert_eq!(svb16_decode(&packed, samples.len()).unwrap(), samples);
+        assert!(svb16_decode(&packed[..3], samples.len()).is_err());
+    }
+
+    /// Stores signal as little endian bytes, to exercise codecs from outside the built-ins.
+    #[derive(Debug)]
+    struct BytesCodec;
+
+    impl crate::codec::SignalCodec for BytesCodec {
+        fn extension_name(&self) -> Option<&str> {
+            Some("podders.test.bytes")
+        }
+
+        fn data_type(&self) -> arrow::datatypes::DataType {
+            arrow::datatypes::DataType::LargeBinary
+        }
+
+        fn encode(&self, samples: &Int16Array) -> Result<arrow::array::ArrayRef, Box<dyn Error>> {
+            let bytes: Vec<u8> = samples
+                .values()
+                .iter()
+                .flat_map(|x| x.to_le_bytes())
+                .collect();
+            Ok(Arc::new(arrow::array::LargeBinaryArray::from_vec(vec![
+                &bytes,
+            ])))
+        }
+
+        fn decode(
+            &self,
+            column: &dyn Array,
+            row: usize,
+            _num_samples: usize,
+        ) -> Result<Vec<i16>, Box<dyn Error>> {
+            let bytes = column
+                .as_any()
+                .downcast_ref::<arrow::array::LargeBinaryArray>()
+                .unwrap()
+                .value(row);
+            Ok(bytes
+                .chunks_exact(2)
+                .map(|x| i16::from_le_bytes([x[0], x[1]]))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_signal_codecs() {
+        use crate::codec::{Vbz, EXTENSION_NAME_KEY};
+
+        let mut signal_table_lengths = vec![];
+        let codecs: [Option<Arc<dyn SignalCodec>>; 3] =
+            [None, Some(Arc::new(Vbz)), Some(Arc::new(BytesCodec))];
+        for (i, codec) in codecs.into_iter().enumerate() {
+            let path = std::env::temp_dir().join(format!("podders_signal_codec_{i}.pod5"));
+            let path = path.to_str().unwrap();
+            let options = WriterOptions {
+                signal_codec: codec.clone(),
+                ..Default::default()
+            };
+            let read = dummy_read_row(None).unwrap();
+            let (pod5, mut reader) =
+                write_reads(path, options, dummy_run_info(), std::slice::from_ref(&read));
+            signal_table_lengths.push(pod5.signal_table.length);
+            let schema = reader.table_schema(ContentType::SignalTable).unwrap();
+            let signal_field = schema.field_with_name("signal").unwrap();
+            assert_eq!(
+                signal_field
+                    .metadata()
+                    .get(EXTENSION_NAME_KEY)
+                    .map(String::as_str),
+                codec.as_ref().and_then(|x| x.extension_name())
+            );
+            if i == 2 {
+                // Custom codecs are only known to readers they are registered with
+                assert!(reader.reads().is_err());
+                reader.register_codec(Arc::new(BytesCodec));
+            }
+            assert_eq!(reader.reads().unwrap(), vec![read]);
+        }
+        assert!(signal_table_lengths[1] < signal_table_lengths[0] / 2);
+    }
+
+    #[test]
+    fn test_lossy_signal() {
+        use crate::codec::{LossyVbz, Vbz};
+
+        let mut signal_table_lengths = vec![];
+        let codecs: [Arc<dyn SignalCodec>; 2] =
+            [Arc::new(Vbz), Arc::new(LossyVbz::new(4).unwrap())];
+        for (i, codec) in codecs.into_iter().enumerate() {
+            let path = std::env::temp_dir().join(format!("podders_lossy_signal_{i}.pod5"));
+            let path = path.to_str().unwrap();
+            let options = WriterOptions {
+                signal_codec: Some(codec),
+                ..Default::default()
+            };
+            let read = dummy_read_row(None).unwrap();
+            let (pod5, mut reader) =
+                write_reads(path, options, dummy_run_info(), std::slice::from_ref(&read));
+            signal_table_lengths.push(pod5.signal_table.length);
+            assert_eq!(reader.signal_codec().unwrap().dropped_bits(), 4 * i as u32);
+            let read_back = reader.reads().unwrap().remove(0);
+            assert_eq!(read_back.signal_.len(), read.signal_.len());
+            for (lossy, exact) in read_back.signal_.iter().zip(&read.signal_) {
+                assert!((lossy - exact).abs() <= 8);
+            }
+        }
+        assert!(signal_table_lengths[1] < signal_table_lengths[0]);
+    }
+
+    #[test]
+    fn test_quantise_pa() {
+        use crate::calibration::{Calibration, ClippingStats};
+
+        let run_info = dummy_run_info();
+        let read = dummy_read_row(None).unwrap();
+        let calibration = Calibration::new(-264.0, 0.187).unwrap();
+        let pa: Vec<f32> = read.signal_.iter().map(|&x| calibration.to_pa(x)).collect();
+        let (builder, clipping) =
+            ReadInfo::builder_from_pa(read.read_id, &pa, calibration, &run_info);
+        let quantised = builder.build().unwrap();
+        assert_eq!(quantised.signal_, read.signal_);
+        assert_eq!(quantised.calibration(), calibration);
+        assert_eq!(clipping.clipped(), 0);
+
+        let (signal, clipping) =
+            calibration.quantise(&[f32::NEG_INFINITY, 0.0, f32::INFINITY, f32::NAN], -10, 300);
+        assert_eq!(signal, vec![-10, 264, 300, 264]);
+        assert_eq!(
+            clipping,
+            ClippingStats {
+                samples: 4,
+                below: 1,
+                above: 1,
+                nan: 1
+            }
+        );
+        assert_eq!(clipping.clipped_fraction(), 0.75);
+    }
+
+    #[test]
+    fn test_signal_pa() {
+        use crate::calibration::Calibration;
+
+        let calibration = Calibration::new(-264.0, 0.187).unwrap();
+        // An odd length, so the vectorised conversion has a remainder
+        let adc: Vec<i16> = (-500..501).collect();
+        let expected: Vec<f32> = adc.iter().map(|&x| calibration.to_pa(x)).collect();
+        assert_eq!(calibration.to_pa_vec(&adc), expected);
+
+        let path = std::env::temp_dir().join("podders_signal_pa.pod5");
+        let read = dummy_read_row(None).unwrap();
+        let (_, mut reader) = write_reads(
+            path.to_str().unwrap(),
+            Default::default(),
+            dummy_run_info(),
+            std::slice::from_ref(&read),
+        );
+        let signal_pa = reader.signal_pa().unwrap();
+        assert_eq!(signal_pa[&read.read_id], read.signal_pa());
+    }
+
+    #[test]
+    fn test_normalisation() {
+        use crate::normalisation::{windows, Normalisation, Scaling};
+
+        let read = dummy_read_row(None).unwrap();
+        let pa = read.signal_pa();
+        let tracked = read.normalised_signal(Normalisation::Tracked).unwrap();
+        assert_eq!(tracked[0], (pa[0] - 0.1) / 1.0);
+
+        let normalised = read.normalised_signal(Normalisation::MedianMad).unwrap();
+        let scaling = Scaling::median_mad(&normalised).unwrap();
+        assert!(scaling.shift.abs() < 1e-3);
+        assert!((scaling.scale - 1.0).abs() < 1e-3);
+
+        let windows = windows(&normalised, 4000, 400).unwrap();
+        assert_eq!(windows[0].start, 0);
+        assert_eq!(windows.last().unwrap().start + 4000, normalised.len());
+        for pair in windows.windows(2) {
+            assert!(pair[1].start - pair[0].start <= 3600);
+            assert_eq!(pair[1].samples.len(), 4000);
+        }
+    }
+
+    #[test]
+    fn test_resample_file() {
+        use crate::resample::{resample_file, Resampler};
+        use crate::run_info::ContextTag;
+
+        let read = dummy_read_row(None).unwrap();
+        let identity = Resampler::new(5000, 5000).unwrap();
+        assert_eq!(identity.resample_read(&read, &dummy_run_info()), read);
+        // The filter overshoots a step, but not past the ADC range
+        let step: Vec<i16> = [0; 200].into_iter().chain([4095; 200]).collect();
+        let resampled = Resampler::new(4000, 5000).unwrap().resample(&step, 0, 4095);
+        assert!(resampled.contains(&0) && resampled.contains(&4095));
+
+        let input = std::env::temp_dir().join("podders_resample_in.pod5");
+        let output = std::env::temp_dir().join("podders_resample_out.pod5");
+        let (input, output) = (input.to_str().unwrap(), output.to_str().unwrap());
+        let mut run_info = dummy_run_info();
+        run_info.sample_rate = 4000;
+        run_info.set_context_tag(ContextTag::SampleFrequency, "4000");
+        (run_info.adc_min, run_info.adc_max) = (-4096, 4095);
+        let mut read = read;
+        read.start = 4000;
+        read.signal_ = [0; 2000].into_iter().chain([4095; 2000]).collect();
+        read.num_samples = 4000;
+        // Only written leniently
+        read.num_reads_since_mux_change = read.read_number + 1;
+        let table_metadata =
+            |table: ContentType| HashMap::from([("table".to_string(), format!("{table:?}"))]);
+        let tables = [
+            ContentType::RunInfoTable,
+            ContentType::ReadsTable,
+            ContentType::SignalTable,
+        ];
+        let options = WriterOptions {
+            validation: ValidationPolicy::Lenient,
+            table_schema_metadata: tables
+                .into_iter()
+                .map(|table| (table, table_metadata(table)))
+                .collect(),
+            ..Default::default()
+        };
+        let (_, mut reader) = write_reads(
+            input,
+            options,
+            run_info.clone(),
+            std::slice::from_ref(&read),
+        );
+        assert_eq!(reader.run_infos().unwrap(), vec![run_info]);
+
+        assert!(resample_file(input, output, 5000, ValidationPolicy::Strict).is_err());
+        resample_file(input, output, 5000, ValidationPolicy::Lenient).unwrap();
+        let mut reader = Pod5Reader::open(output).unwrap();
+        for table in tables {
+            assert_eq!(
+                reader.custom_metadata(table).unwrap(),
+                table_metadata(table)
+            );
+        }
+        let run_info = reader.run_infos().unwrap().remove(0);
+        assert_eq!(run_info.sample_rate, 5000);
+        assert_eq!(run_info.sample_frequency(), Some(5000));
+        let resampled = reader.reads().unwrap().remove(0);
+        assert_eq!(resampled.start, 5000);
+        assert_eq!(resampled.num_samples, (read.num_samples * 5).div_ceil(4));
+        assert_eq!(resampled.signal_.len() as u64, resampled.num_samples);
+        assert!(resampled.signal_.iter().all(|x| (-4096..=4095).contains(x)));
+    }
+
+    #[test]
+    fn test_split_reads() {
+        use crate::reads::PARENT_READ_ID;
+
+        let mut read = dummy_read_row(None).unwrap();
+        read.trim(100, 0).unwrap();
+        let children = read.split(&[1000, 2000]).unwrap();
+        let grandchildren = children[2].split(&[10]).unwrap();
+        assert_eq!(grandchildren[1].parent_read_id(), Some(read.read_id));
+        assert_eq!(grandchildren[1].start, children[2].start + 10);
+
+        let path = std::env::temp_dir().join("podders_split_reads.pod5");
+        let path = path.to_str().unwrap();
+        let options = WriterOptions {
+            extension_columns: vec![(PARENT_READ_ID.to_string(), ExtensionType::Uuid)],
+            ..Default::default()
+        };
+        let (_, mut reader) = write_reads(path, options, dummy_run_info(), &children);
+        let read_back = reader.reads().unwrap();
+        assert_eq!(read_back, children);
+        let signal: Vec<i16> = read_back.iter().flat_map(|x| x.signal_.clone()).collect();
+        assert_eq!(signal, read.signal_);
+        assert!(read_back
+            .iter()
+            .all(|x| x.parent_read_id() == Some(read.read_id)));
+    }
+
+    #[test]
+    fn test_simulate_reads() {
+        use crate::normalisation::Normalisation;
+        use crate::simulate::{KmerLevel, KmerModel, Simulator, SimulatorOptions};
+
+        let bases = ["A", "C", "G", "T"];
+        let levels = bases.iter().enumerate().flat_map(|(i, first)| {
+            bases.iter().enumerate().map(move |(j, second)| {
+                let mean = (i * 4 + j) as f32 / 5.0 - 1.5;
+                (format!("{first}{second}"), KmerLevel { mean, stdv: None })
+            })
+        });
+        let model = KmerModel::new(levels).unwrap();
+        let options = SimulatorOptions {
+            noise_stdv: 0.05,
+            level_shift: 90.0,
+            level_scale: 12.0,
+            seed: Some(7),
+            ..Default::default()
+        };
+        let sequence: String = (0..200).map(|i| bases[(i * 7 + i / 3) % 4]).collect();
+        let run_info = dummy_run_info();
+        let mut simulator = Simulator::new(model.clone(), options.clone());
+        let simulated = simulator.simulate(&sequence, &run_info).unwrap();
+        // Compared field by field, as the simulated reads' median before is unknown, i.e. NaN
+        let again = Simulator::new(model.clone(), options.clone())
+            .simulate(&sequence, &run_info)
+            .unwrap();
+        assert_eq!(again.read.read_id, simulated.read.read_id);
+        assert_eq!(again.read.signal_, simulated.read.signal_);
+        assert_eq!(again.dwells, simulated.dwells);
+        assert!(simulator.simulate("ACNT", &run_info).is_err());
+        // Unseeded simulators do not repeat read ids
+        let unseeded = SimulatorOptions {
+            seed: None,
+            ..options.clone()
+        };
+        let ids: HashSet<Uuid> = (0..2)
+            .map(|_| {
+                Simulator::new(model.clone(), unseeded.clone())
+                    .simulate(&sequence, &run_info)
+                    .unwrap()
+                    .read
+                    .read_id
+            })
+            .collect();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(simulated.dwells.len(), sequence.len() - 1);
+        assert_eq!(simulated.clipping.clipped(), 0);
+
+        let normalised = simulated
+            .read
+            .normalised_signal(Normalisation::Predicted)
+            .unwrap();
+        let mut offset = 0;
+        for (i, dwell) in simulated.dwells.iter().enumerate() {
+            let segment = &normalised[offset..offset + dwell];
+            let mean = segment.iter().sum::<f32>() / *dwell as f32;
+            let level = model.level(&sequence[i..i + 2]).unwrap().mean;
+            assert!(
+                (mean - level).abs() < 0.2,
+                "k-mer {i} has mean {mean}, not {level}"
+            );
+            offset += dwell;
+        }
+
+        let path = std::env::temp_dir().join("podders_simulate_reads.pod5");
+        let (_, mut reader) = write_reads(
+            path.to_str().unwrap(),
+            Default::default(),
+            run_info,
+            std::slice::from_ref(&simulated.read),
+        );
+        let read_back = reader.reads().unwrap().remove(0);
+        assert_eq!(read_back.read_id, simulated.read.read_id);
+        assert_eq!(read_back.signal_, simulated.read.signal_);
+    }
+
+    #[test]
+    fn test_writing_empty_file() {
+        let path = std::env::temp_dir().join("podders_empty.pod5");
+        let path = path.to_str().unwrap();
+        let mut pod5 = Pod5File::new(path).unwrap();
+        pod5.write_run_info_to_ipc().unwrap();
+        pod5.write_reads_to_ipc().unwrap();
+        pod5.write_signal_to_ipc().unwrap();
+        pod5.write_footer().unwrap();
+
+        for (table, num_fields) in [
+            (ContentType::RunInfoTable, 20),
+            (ContentType::ReadsTable, 21),
+            (ContentType::SignalTable, 3),
+        ] {
+            let file_info = read_pod5_footer(path, table);
+            let mut file = File::open(path).unwrap();
+            let mut buffer = vec![0; file_info.length as usize];
+            file.seek(SeekFrom::Start(file_info.offset)).unwrap();
+            file.read_exact(&mut buffer).unwrap();
+            let reader = FileReader::try_new(std::io::Cursor::new(buffer), None).unwrap();
+            assert_eq!(reader.schema().fields().len(), num_fields);
+            assert_eq!(reader.num_batches(), 0);
+        }
+    }
     #[test]
     fn test_reading_signal_table() {
         let file_info = read_pod5_footer("test_builder.pod5", ContentType::SignalTable);
@@ -0,0 +1,141 @@
+//! Normalisation of a read's pA signal, and its division into windows for basecaller models.
+//!
+//! A [`Scaling`] normalises pA signal as `(pA - shift) / scale`. It can be the scaling MinKNOW
+//! predicted or tracked for a read, stored in `ReadInfo`, or estimated from the signal itself with
+//! [`Scaling::median_mad`]. See [`crate::reads::ReadInfo::normalised_signal`].
+
+use std::error::Error;
+
+/// Where the scaling used to normalise a read's signal comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalisation {
+    /// `predicted_scaling_shift` and `predicted_scaling_scale` of the read.
+    Predicted,
+    /// `tracked_scaling_shift` and `tracked_scaling_scale` of the read.
+    Tracked,
+    /// The median and scaled median absolute deviation of the read's pA signal.
+    MedianMad,
+}
+
+/// Normalises pA signal as `(pA - shift) / scale`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scaling {
+    pub shift: f32,
+    pub scale: f32,
+}
+
+/// Scales the median absolute deviation to the standard deviation, for normally distributed signal.
+const MAD_TO_STD: f32 = 1.4826;
+
+impl Scaling {
+    /// A scaling with a finite shift and a finite, non-zero scale.
+    ///
+    /// Reads store NaN scalings when they are not known, which are refused here.
+    pub fn new(shift: f32, scale: f32) -> Result<Self, Box<dyn Error>> {
+        if !shift.is_finite() || !scale.is_finite() || scale == 0.0 {
+            return Err(format!(
+                "scaling must have a finite shift and a finite, non-zero scale, got shift {shift} and scale {scale}"
+            )
+            .into());
+        }
+        Ok(Scaling { shift, scale })
+    }
+
+    /// The median of the signal, with its median absolute deviation scaled to a standard deviation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::normalisation::Scaling;
+    ///
+    /// let scaling = Scaling::median_mad(&[1.0, 2.0, 3.0, 4.0, 100.0]).unwrap();
+    /// assert_eq!(scaling.shift, 3.0);
+    /// assert!((scaling.scale - 1.4826).abs() < 1e-6);
+    /// assert!(Scaling::median_mad(&[2.0; 10]).is_err());
+    /// ```
+    pub fn median_mad(pa: &[f32]) -> Result<Self, Box<dyn Error>> {
+        let mut sorted = pa.to_vec();
+        let shift = median(&mut sorted).ok_or("cannot take the median of no signal")?;
+        for sample in sorted.iter_mut() {
+            *sample = (*sample - shift).abs();
+        }
+        let mad = median(&mut sorted).ok_or("cannot take the median of no signal")?;
+        Scaling::new(shift, mad * MAD_TO_STD)
+    }
+
+    /// Normalise pA signal.
+    pub fn apply(&self, pa: &[f32]) -> Vec<f32> {
+        pa.iter()
+            .map(|&sample| (sample - self.shift) / self.scale)
+            .collect()
+    }
+}
+
+/// The median of the samples, reordering them. NaN samples sort last.
+fn median(samples: &mut [f32]) -> Option<f32> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable_by(f32::total_cmp);
+    let middle = samples.len() / 2;
+    if samples.len() % 2 == 1 {
+        Some(samples[middle])
+    } else {
+        Some((samples[middle - 1] + samples[middle]) / 2.0)
+    }
+}
+
+/// A window of signal, as input to a basecaller model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Window<'a> {
+    /// Offset of the first sample of the window in the signal.
+    pub start: usize,
+    pub samples: &'a [f32],
+}
+
+/// Divide signal into windows of `window_len` samples, each overlapping the previous by at least `overlap`.
+///
+/// Windows step by `window_len - overlap`, with the last aligned to the end of the signal so every
+/// window is full length, overlapping the one before it by more if needed. Signal shorter than a
+/// window is a single, shorter, window, to be padded as the model requires.
+///
+/// # Examples
+///
+/// ```
+/// use podders::normalisation::windows;
+///
+/// let signal: Vec<f32> = (0..10).map(|x| x as f32).collect();
+/// let starts: Vec<usize> = windows(&signal, 4, 1).unwrap().iter().map(|w| w.start).collect();
+/// assert_eq!(starts, vec![0, 3, 6]);
+/// let starts: Vec<usize> = windows(&signal, 4, 2).unwrap().iter().map(|w| w.start).collect();
+/// assert_eq!(starts, vec![0, 2, 4, 6]);
+/// assert!(windows(&signal, 4, 4).is_err());
+/// ```
+pub fn windows(
+    signal: &[f32],
+    window_len: usize,
+    overlap: usize,
+) -> Result<Vec<Window<'_>>, Box<dyn Error>> {
+    if overlap >= window_len {
+        return Err(format!(
+            "window overlap ({overlap}) must be less than the window length ({window_len})"
+        )
+        .into());
+    }
+    if signal.len() <= window_len {
+        return Ok(vec![Window {
+            start: 0,
+            samples: signal,
+        }]);
+    }
+    let last = signal.len() - window_len;
+    let mut starts: Vec<usize> = (0..last).step_by(window_len - overlap).collect();
+    starts.push(last);
+    Ok(starts
+        .into_iter()
+        .map(|start| Window {
+            start,
+            samples: &signal[start..start + window_len],
+        })
+        .collect())
+}
@@ -0,0 +1,390 @@
+//! Reading back the tables of a pod5 file.
+//!
+//! The footer is parsed once when the file is opened, after which each embedded table
+//! can be read as Arrow `RecordBatch`es, along with its schema and metadata.
+
+use arrow::{
+    array::{
+        Array, BooleanArray, FixedSizeBinaryArray, Float32Array, Int16Array, ListArray, MapArray,
+        StringArray, TimestampMillisecondArray, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+    },
+    datatypes::SchemaRef,
+    ipc::reader::FileReader,
+    record_batch::RecordBatch,
+};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::File,
+    io::{Cursor, Read, Seek, SeekFrom},
+    sync::Arc,
+};
+use uuid::Uuid;
+
+use crate::{
+    codec::{codec_for_field, SignalCodec},
+    footer::{read_footer_buffer, FileInfo},
+    reads::{
+        decode_dictionary_column, EndReason, ExtensionType, ExtensionValue, PoreType, ReadInfo,
+        SPEC_READ_FIELDS,
+    },
+    root_as_footer,
+    run_info::RunInfoData,
+    ContentType,
+};
+
+/// Prefix of the schema metadata keys reserved by the pod5 specification.
+pub const MINKNOW_METADATA_PREFIX: &str = "MINKNOW:";
+
+/// An open pod5 file, ready to read its embedded tables.
+pub struct Pod5Reader {
+    /// File handle the tables are read from.
+    file: File,
+    /// Offset and length of each embedded table, from the footer.
+    tables: HashMap<ContentType, FileInfo>,
+    /// Identifier of the file, from the footer.
+    file_identifier: String,
+    /// Software that wrote the file, from the footer.
+    software: String,
+    /// Pod5 version of the file, from the footer.
+    pod5_version: String,
+    /// Signal codecs registered beyond the built-in ones.
+    codecs: Vec<Arc<dyn SignalCodec>>,
+}
+
+impl Pod5Reader {
+    /// Opens a pod5 file and parses its footer.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let mut reader = Pod5Reader::open("path/to/file.pod5")?;
+    /// let reads = reader.read_table(ContentType::ReadsTable)?;
+    /// ```
+    pub fn open(filepath: &str) -> Result<Self, Box<dyn Error>> {
+        let mut file = File::open(filepath)?;
+        let buf = read_footer_buffer(&mut file)?;
+        let footer = root_as_footer(&buf)?;
+        let tables = footer
+            .contents()
+            .ok_or("pod5 footer has no embedded tables")?
+            .iter()
+            .map(|x| {
+                (
+                    x.content_type(),
+                    FileInfo {
+                        offset: x.offset() as u64,
+                        length: x.length() as u64,
+                    },
+                )
+            })
+            .collect();
+        Ok(Pod5Reader {
+            file_identifier: footer.file_identifier().unwrap_or_default().to_string(),
+            software: footer.software().unwrap_or_default().to_string(),
+            pod5_version: footer.pod5_version().unwrap_or_default().to_string(),
+            file,
+            tables,
+            codecs: vec![],
+        })
+    }
+
+    /// Register a signal codec, to read signal stored with it. The built-in codecs are always known.
+    pub fn register_codec(&mut self, codec: Arc<dyn SignalCodec>) {
+        self.codecs.push(codec);
+    }
+
+    /// Identifier of the file, as recorded in the footer.
+    pub fn file_identifier(&self) -> &str {
+        &self.file_identifier
+    }
+
+    /// Software that wrote the file, as recorded in the footer.
+    pub fn software(&self) -> &str {
+        &self.software
+    }
+
+    /// Pod5 version of the file, as recorded in the footer.
+    pub fn pod5_version(&self) -> &str {
+        &self.pod5_version
+    }
+
+    /// Open an Arrow IPC reader over one of the embedded tables.
+    fn table_reader(
+        &mut self,
+        table: ContentType,
+    ) -> Result<FileReader<Cursor<Vec<u8>>>, Box<dyn Error>> {
+        let file_info = self
+            .tables
+            .get(&table)
+            .ok_or_else(|| format!("pod5 file has no {table:?}"))?;
+        self.file.seek(SeekFrom::Start(file_info.offset))?;
+        let mut buffer = vec![0; file_info.length as usize];
+        self.file.read_exact(&mut buffer)?;
+        Ok(FileReader::try_new(Cursor::new(buffer), None)?)
+    }
+
+    /// Schema of one of the embedded tables, including its metadata.
+    pub fn table_schema(&mut self, table: ContentType) -> Result<SchemaRef, Box<dyn Error>> {
+        Ok(self.table_reader(table)?.schema())
+    }
+
+    /// Every `RecordBatch` of one of the embedded tables.
+    pub fn read_table(&mut self, table: ContentType) -> Result<Vec<RecordBatch>, Box<dyn Error>> {
+        let mut batches = vec![];
+        for batch in self.table_reader(table)? {
+            batches.push(batch?);
+        }
+        Ok(batches)
+    }
+
+    /// The user defined schema metadata of one of the embedded tables, i.e. every key
+    /// not reserved by the pod5 specification.
+    pub fn custom_metadata(
+        &mut self,
+        table: ContentType,
+    ) -> Result<HashMap<String, String>, Box<dyn Error>> {
+        Ok(self
+            .table_schema(table)?
+            .metadata()
+            .iter()
+            .filter(|(key, _)| !key.starts_with(MINKNOW_METADATA_PREFIX))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+
+    /// The codec the signal table is stored with, e.g. to check `SignalCodec::dropped_bits`.
+    pub fn signal_codec(&mut self) -> Result<Arc<dyn SignalCodec>, Box<dyn Error>> {
+        let schema = self.table_schema(ContentType::SignalTable)?;
+        codec_for_field(schema.field_with_name("signal")?, &self.codecs)
+    }
+
+    /// The samples of every row of the signal table, in row order, decoded with the codec
+    /// named by the signal field.
+    pub fn signal_rows(&mut self) -> Result<Vec<Vec<i16>>, Box<dyn Error>> {
+        let codec = self.signal_codec()?;
+        let mut rows = vec![];
+        for batch in self.read_table(ContentType::SignalTable)? {
+            let signal = batch_column(&batch, "signal")?;
+            let samples = column::<UInt32Array>(&batch, "samples")?;
+            for row in 0..batch.num_rows() {
+                rows.push(codec.decode(signal, row, samples.value(row) as usize)?);
+            }
+        }
+        Ok(rows)
+    }
+
+    /// The signal of every read in the file in pA, by read id.
+    pub fn signal_pa(&mut self) -> Result<HashMap<Uuid, Vec<f32>>, Box<dyn Error>> {
+        Ok(self
+            .reads()?
+            .iter()
+            .map(|read| (read.read_id, read.signal_pa()))
+            .collect())
+    }
+
+    /// Every run info in the file, in the order of the run info table.
+    pub fn run_infos(&mut self) -> Result<Vec<RunInfoData>, Box<dyn Error>> {
+        let mut run_infos = vec![];
+        for batch in self.read_table(ContentType::RunInfoTable)? {
+            let string = |name: &str| column::<StringArray>(&batch, name);
+            let acquisition_id = string("acquisition_id")?;
+            let acquisition_start_time =
+                column::<TimestampMillisecondArray>(&batch, "acquisition_start_time")?;
+            let adc_max = column::<Int16Array>(&batch, "adc_max")?;
+            let adc_min = column::<Int16Array>(&batch, "adc_min")?;
+            let context_tags = column::<MapArray>(&batch, "context_tags")?;
+            let experiment_name = string("experiment_name")?;
+            let flow_cell_id = string("flow_cell_id")?;
+            let flow_cell_product_code = string("flow_cell_product_code")?;
+            let protocol_name = string("protocol_name")?;
+            let protocol_run_id = string("protocol_run_id")?;
+            let protocol_start_time =
+                column::<TimestampMillisecondArray>(&batch, "protocol_start_time")?;
+            let sample_id = string("sample_id")?;
+            let sample_rate = column::<UInt16Array>(&batch, "sample_rate")?;
+            let sequencing_kit = string("sequencing_kit")?;
+            let sequencer_position = string("sequencer_position")?;
+            let sequencer_position_type = string("sequencer_position_type")?;
+            let software = string("software")?;
+            let system_name = string("system_name")?;
+            let system_type = string("system_type")?;
+            let tracking_id = column::<MapArray>(&batch, "tracking_id")?;
+
+            for row in 0..batch.num_rows() {
+                run_infos.push(RunInfoData {
+                    acquisition_id: acquisition_id.value(row).to_string(),
+                    acquisition_start_time: acquisition_start_time.value(row),
+                    adc_max: adc_max.value(row),
+                    adc_min: adc_min.value(row),
+                    context_tags: map_value(context_tags, row)?,
+                    experiment_name: experiment_name.value(row).to_string(),
+                    flow_cell_id: flow_cell_id.value(row).to_string(),
+                    flow_cell_product_code: flow_cell_product_code.value(row).to_string(),
+                    protocol_name: protocol_name.value(row).to_string(),
+                    protocol_run_id: protocol_run_id.value(row).to_string(),
+                    protocol_start_time: protocol_start_time.value(row),
+                    sample_id: sample_id.value(row).to_string(),
+                    sample_rate: sample_rate.value(row),
+                    sequencing_kit: sequencing_kit.value(row).to_string(),
+                    sequencer_position: sequencer_position.value(row).to_string(),
+                    sequencer_position_type: sequencer_position_type.value(row).to_string(),
+                    software: software.value(row).to_string(),
+                    system_name: system_name.value(row).to_string(),
+                    system_type: system_type.value(row).to_string(),
+                    tracking_id: map_value(tracking_id, row)?,
+                });
+            }
+        }
+        Ok(run_infos)
+    }
+
+    /// The columns of the reads table beyond the pod5 specification, with the type they are read
+    /// back as. Suitable for `WriterOptions::extension_columns`, to write the reads back out.
+    pub fn extension_columns(&mut self) -> Result<Vec<(String, ExtensionType)>, Box<dyn Error>> {
+        Ok(self
+            .table_schema(ContentType::ReadsTable)?
+            .fields()
+            .iter()
+            .filter(|field| !SPEC_READ_FIELDS.contains(&field.name().as_str()))
+            .map(|field| {
+                (
+                    field.name().clone(),
+                    ExtensionType::from_data_type(field.data_type()),
+                )
+            })
+            .collect())
+    }
+
+    /// Every read in the file, with its signal.
+    ///
+    /// Columns of the reads table that are not in the pod5 specification, such as those written
+    /// by other tools, are surfaced in `ReadInfo::extensions` rather than refused.
+    pub fn reads(&mut self) -> Result<Vec<ReadInfo>, Box<dyn Error>> {
+        let signal_rows = self.signal_rows()?;
+        let mut reads = vec![];
+        for batch in self.read_table(ContentType::ReadsTable)? {
+            let read_id = column::<FixedSizeBinaryArray>(&batch, "read_id")?;
+            let signal = column::<ListArray>(&batch, "signal")?;
+            let channel = column::<UInt16Array>(&batch, "channel")?;
+            let well = column::<UInt8Array>(&batch, "well")?;
+            let pore_type: Vec<PoreType> =
+                decode_dictionary_column(batch_column(&batch, "pore_type")?)?;
+            let calibration_offset = column::<Float32Array>(&batch, "calibration_offset")?;
+            let calibration_scale = column::<Float32Array>(&batch, "calibration_scale")?;
+            let read_number = column::<UInt32Array>(&batch, "read_number")?;
+            let start = column::<UInt64Array>(&batch, "start")?;
+            let median_before = column::<Float32Array>(&batch, "median_before")?;
+            let tracked_scaling_scale = column::<Float32Array>(&batch, "tracked_scaling_scale")?;
+            let tracked_scaling_shift = column::<Float32Array>(&batch, "tracked_scaling_shift")?;
+            let predicted_scaling_scale =
+                column::<Float32Array>(&batch, "predicted_scaling_scale")?;
+            let predicted_scaling_shift =
+                column::<Float32Array>(&batch, "predicted_scaling_shift")?;
+            let num_reads_since_mux_change =
+                column::<UInt32Array>(&batch, "num_reads_since_mux_change")?;
+            let time_since_mux_change = column::<Float32Array>(&batch, "time_since_mux_change")?;
+            let num_minknow_events = column::<UInt64Array>(&batch, "num_minknow_events")?;
+            let end_reason: Vec<EndReason> =
+                decode_dictionary_column(batch_column(&batch, "end_reason")?)?;
+            let end_reason_forced = column::<BooleanArray>(&batch, "end_reason_forced")?;
+            let run_info: Vec<String> =
+                decode_dictionary_column(batch_column(&batch, "run_info")?)?;
+            let num_samples = column::<UInt64Array>(&batch, "num_samples")?;
+            let open_pore_level = match batch.column_by_name("open_pore_level") {
+                Some(_) => Some(column::<Float32Array>(&batch, "open_pore_level")?),
+                None => None,
+            };
+            let schema = batch.schema();
+            let extension_columns: Vec<(&String, &Arc<dyn Array>)> = schema
+                .fields()
+                .iter()
+                .zip(batch.columns())
+                .filter(|(field, _)| !SPEC_READ_FIELDS.contains(&field.name().as_str()))
+                .map(|(field, array)| (field.name(), array))
+                .collect();
+
+            for row in 0..batch.num_rows() {
+                let signal_index = signal.value(row);
+                let signal_index = signal_index
+                    .as_any()
+                    .downcast_ref::<UInt64Array>()
+                    .ok_or("signal column is not a list of uint64")?;
+                let mut signal_ = vec![];
+                for index in signal_index.values() {
+                    signal_.extend_from_slice(
+                        signal_rows
+                            .get(*index as usize)
+                            .ok_or_else(|| format!("signal row {index} does not exist"))?,
+                    );
+                }
+                let extensions = extension_columns
+                    .iter()
+                    .filter_map(|(name, array)| {
+                        ExtensionValue::from_array(array.as_ref(), row)
+                            .map(|value| (name.to_string(), value))
+                    })
+                    .collect();
+                reads.push(ReadInfo {
+                    read_id: Uuid::from_slice(read_id.value(row))?,
+                    pore_type: pore_type[row].clone(),
+                    signal_,
+                    channel: channel.value(row),
+                    well: well.value(row),
+                    calibration_offset: calibration_offset.value(row),
+                    calibration_scale: calibration_scale.value(row),
+                    read_number: read_number.value(row),
+                    start: start.value(row),
+                    median_before: median_before.value(row),
+                    tracked_scaling_shift: tracked_scaling_shift.value(row),
+                    tracked_scaling_scale: tracked_scaling_scale.value(row),
+                    predicted_scaling_shift: predicted_scaling_shift.value(row),
+                    predicted_scaling_scale: predicted_scaling_scale.value(row),
+                    num_reads_since_mux_change: num_reads_since_mux_change.value(row),
+                    time_since_mux_change: time_since_mux_change.value(row),
+                    num_minknow_events: num_minknow_events.value(row),
+                    end_reason: end_reason[row].clone(),
+                    end_reason_forced: end_reason_forced.value(row),
+                    run_info: run_info[row].clone(),
+                    num_samples: num_samples.value(row),
+                    open_pore_level: open_pore_level
+                        .map(|x| x.value(row))
+                        .filter(|x| !x.is_nan()),
+                    extensions,
+                });
+            }
+        }
+        Ok(reads)
+    }
+}
+
+/// A column of a table by name.
+fn batch_column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a dyn Array, Box<dyn Error>> {
+    Ok(batch
+        .column_by_name(name)
+        .ok_or_else(|| format!("table has no {name} column"))?
+        .as_ref())
+}
+
+/// The key/values of one row of a map column of strings, such as `context_tags`.
+fn map_value(map: &MapArray, row: usize) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let entries = map.value(row);
+    let strings = |index: usize| {
+        entries
+            .column(index)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .ok_or("map column is not of strings")
+    };
+    let (keys, values) = (strings(0)?, strings(1)?);
+    Ok((0..entries.len())
+        .map(|i| (keys.value(i).to_string(), values.value(i).to_string()))
+        .collect())
+}
+
+/// A column of a table by name, as its concrete array type.
+fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> Result<&'a T, Box<dyn Error>> {
+    batch_column(batch, name)?
+        .as_any()
+        .downcast_ref::<T>()
+        .ok_or_else(|| format!("{name} column has an unexpected type").into())
+}
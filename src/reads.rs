@@ -11,31 +11,52 @@
 //! This module is essential for managing read data in the context of POD5 files, leveraging Apache Arrow's capabilities in Rust.
 
 use crate::{
-    signal::{handle_signal_data, read_int16_from_file},
-    POD5_VERSION, SOFTWARE,
+    _schema_metadata,
+    calibration::{Calibration, ClippingStats},
+    codec::SignalCodec,
+    dictionary::{ReadDictionaries, END_REASON_DICT_ID, PORE_TYPE_DICT_ID, RUN_INFO_DICT_ID},
+    normalisation::{Normalisation, Scaling},
+    run_info::RunInfoData,
+    signal::{handle_signal_data, int16_from_ne_bytes},
+    validation::ValidationError,
+    Pod5Version,
 };
 use arrow::{
     array::{
-        Array, BooleanArray, DictionaryArray, FixedSizeBinaryBuilder, Float32Array, Int16Array,
-        ListArray, ListBuilder, StringArray, UInt16Array, UInt32Array, UInt64Array, UInt64Builder,
-        UInt8Array,
+        new_null_array, Array, BooleanArray, DictionaryArray, FixedSizeBinaryArray,
+        FixedSizeBinaryBuilder, Float32Array, Float64Array, Int16Array, Int64Array, ListArray,
+        ListBuilder, StringArray, UInt16Array, UInt32Array, UInt64Array, UInt64Builder, UInt8Array,
     },
     datatypes::{DataType, Field, Int16Type, Schema},
     record_batch::RecordBatch,
+    util::display::{ArrayFormatter, FormatOptions},
 };
 // use log::debug;
 use std::{
-    collections::{HashMap, HashSet},
+    borrow::Cow,
+    collections::HashMap,
+    convert::Infallible,
     error::Error,
     fmt,
+    str::FromStr,
     sync::Arc,
+    time::{Duration, SystemTime},
 };
 use uuid::Uuid;
 
+/// The pore type of a read, as stored in the `pore_type` dictionary column.
+///
+/// The well known pores have their own variants, any other pore string is carried as `Other`.
+/// Build pore types from strings with `From<&str>` or `parse`, which map the well known strings to
+/// their variants. `Other` holding one of them is written identically, but compares unequal, so
+/// `ReadInfo::validate` reports it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum PoreType {
     R9,
     R10,
     NotSet,
+    /// A pore string other than the well known ones.
+    Other(String),
 }
 impl fmt::Display for PoreType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -46,12 +67,62 @@ impl fmt::Display for PoreType {
                 PoreType::R9 => "R9.4.1",
                 PoreType::R10 => "R10.4.1",
                 PoreType::NotSet => "not-set",
+                PoreType::Other(pore) => pore,
             }
         )
     }
 }
+impl FromStr for PoreType {
+    type Err = Infallible;
 
+    /// Parse a stored pore type string, the inverse of `Display`.
+    ///
+    /// ```
+    /// use podders::reads::PoreType;
+    ///
+    /// assert_eq!("R10.4.1".parse::<PoreType>().unwrap(), PoreType::R10);
+    /// let pore: PoreType = "RNA004".parse().unwrap();
+    /// assert_eq!(pore, PoreType::Other("RNA004".to_string()));
+    /// assert_eq!(pore.to_string(), "RNA004");
+    /// assert_eq!(PoreType::from("not-set"), PoreType::NotSet);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "R9.4.1" => PoreType::R9,
+            "R10.4.1" => PoreType::R10,
+            "not-set" => PoreType::NotSet,
+            pore => PoreType::Other(pore.to_string()),
+        })
+    }
+}
+impl From<&str> for PoreType {
+    fn from(pore: &str) -> Self {
+        pore.parse()
+            .unwrap_or_else(|never: Infallible| match never {})
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for PoreType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_helpers::serialize_display(self, serializer)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PoreType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_helpers::deserialize_from_str(deserializer)
+    }
+}
+
+/// Why a read ended, as stored in the `end_reason` dictionary column.
+///
+/// Covers the pod5 end reason vocabulary, any other end reason string is carried as `Other`.
+/// Build end reasons from strings with `From<&str>` or `parse`, which map the vocabulary to its
+/// variants. `Other` holding one of them is written identically, but compares unequal, so
+/// `ReadInfo::validate` reports it.
 #[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum EndReason {
     UNKNOWN,
     MUX_CHANGE,
@@ -59,6 +130,60 @@ pub enum EndReason {
     DATA_SERVICE_UNBLOCK_MUX_CHANGE,
     SIGNAL_POSITIVE,
     SIGNAL_NEGATIVE,
+    API_REQUEST,
+    DEVICE_DATA_ERROR,
+    ANALYSIS_CONFIG_CHANGE,
+    PAUSED,
+    /// An end reason string outside the pod5 vocabulary.
+    Other(String),
+}
+impl EndReason {
+    /// Every end reason of the pod5 vocabulary, i.e. all but `Other`.
+    pub const KNOWN: [EndReason; 10] = [
+        EndReason::UNKNOWN,
+        EndReason::MUX_CHANGE,
+        EndReason::UNBLOCK_MUX_CHANGE,
+        EndReason::DATA_SERVICE_UNBLOCK_MUX_CHANGE,
+        EndReason::SIGNAL_POSITIVE,
+        EndReason::SIGNAL_NEGATIVE,
+        EndReason::API_REQUEST,
+        EndReason::DEVICE_DATA_ERROR,
+        EndReason::ANALYSIS_CONFIG_CHANGE,
+        EndReason::PAUSED,
+    ];
+
+    /// The named variant of an `Other` holding a string of the vocabulary, otherwise `self`.
+    fn known(&self) -> Cow<'_, EndReason> {
+        match self {
+            EndReason::Other(end_reason) => Cow::Owned(EndReason::from(end_reason.as_str())),
+            _ => Cow::Borrowed(self),
+        }
+    }
+
+    /// Whether the read was ended by an unblock, requested by MinKNOW or by the data service.
+    pub fn is_unblock(&self) -> bool {
+        matches!(
+            *self.known(),
+            EndReason::UNBLOCK_MUX_CHANGE | EndReason::DATA_SERVICE_UNBLOCK_MUX_CHANGE
+        )
+    }
+
+    /// Whether the read ended because of the signal itself, rather than an external event.
+    pub fn is_signal(&self) -> bool {
+        matches!(
+            *self.known(),
+            EndReason::SIGNAL_POSITIVE | EndReason::SIGNAL_NEGATIVE
+        )
+    }
+
+    /// The `end_reason_forced` value pod5 uses for this end reason, forced unless the signal ended
+    /// the read or the end reason is unknown.
+    pub fn default_forced(&self) -> bool {
+        !matches!(
+            *self.known(),
+            EndReason::UNKNOWN | EndReason::SIGNAL_POSITIVE | EndReason::SIGNAL_NEGATIVE
+        )
+    }
 }
 impl fmt::Display for EndReason {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -72,10 +197,420 @@ impl fmt::Display for EndReason {
                 EndReason::DATA_SERVICE_UNBLOCK_MUX_CHANGE => "data_service_unblock_mux_change",
                 EndReason::SIGNAL_POSITIVE => "signal_positive",
                 EndReason::SIGNAL_NEGATIVE => "signal_negative",
+                EndReason::API_REQUEST => "api_request",
+                EndReason::DEVICE_DATA_ERROR => "device_data_error",
+                EndReason::ANALYSIS_CONFIG_CHANGE => "analysis_config_change",
+                EndReason::PAUSED => "paused",
+                EndReason::Other(end_reason) => end_reason,
             }
         )
     }
 }
+impl FromStr for EndReason {
+    type Err = Infallible;
+
+    /// Parse a stored end reason string, the inverse of `Display`.
+    ///
+    /// ```
+    /// use podders::reads::EndReason;
+    ///
+    /// let end_reason: EndReason = "data_service_unblock_mux_change".parse().unwrap();
+    /// assert!(end_reason.is_unblock());
+    /// assert_eq!("paused".parse::<EndReason>().unwrap(), EndReason::PAUSED);
+    /// assert_eq!(EndReason::from("signal_positive"), EndReason::SIGNAL_POSITIVE);
+    /// assert!(EndReason::Other("signal_positive".to_string()).is_signal());
+    /// assert_eq!(
+    ///     "a_new_reason".parse::<EndReason>().unwrap(),
+    ///     EndReason::Other("a_new_reason".to_string())
+    /// );
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "unknown" => EndReason::UNKNOWN,
+            "mux_change" => EndReason::MUX_CHANGE,
+            "unblock_mux_change" => EndReason::UNBLOCK_MUX_CHANGE,
+            "data_service_unblock_mux_change" => EndReason::DATA_SERVICE_UNBLOCK_MUX_CHANGE,
+            "signal_positive" => EndReason::SIGNAL_POSITIVE,
+            "signal_negative" => EndReason::SIGNAL_NEGATIVE,
+            "api_request" => EndReason::API_REQUEST,
+            "device_data_error" => EndReason::DEVICE_DATA_ERROR,
+            "analysis_config_change" => EndReason::ANALYSIS_CONFIG_CHANGE,
+            "paused" => EndReason::PAUSED,
+            end_reason => EndReason::Other(end_reason.to_string()),
+        })
+    }
+}
+impl From<&str> for EndReason {
+    fn from(end_reason: &str) -> Self {
+        end_reason
+            .parse()
+            .unwrap_or_else(|never: Infallible| match never {})
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for EndReason {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_helpers::serialize_display(self, serializer)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for EndReason {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_helpers::deserialize_from_str(deserializer)
+    }
+}
+
+/// Names of the fields the pod5 specification defines on the reads table, across every supported version.
+/// Any other field on a reads table is an extension column.
+pub const SPEC_READ_FIELDS: [&str; 22] = [
+    "read_id",
+    "signal",
+    "channel",
+    "well",
+    "pore_type",
+    "calibration_offset",
+    "calibration_scale",
+    "read_number",
+    "start",
+    "median_before",
+    "tracked_scaling_scale",
+    "tracked_scaling_shift",
+    "predicted_scaling_scale",
+    "predicted_scaling_shift",
+    "num_reads_since_mux_change",
+    "time_since_mux_change",
+    "num_minknow_events",
+    "end_reason",
+    "end_reason_forced",
+    "run_info",
+    "num_samples",
+    "open_pore_level",
+];
+
+/// Name of the extension column holding the `read_id` a read was split from, see [`ReadInfo::split`].
+pub const PARENT_READ_ID: &str = "parent_read_id";
+
+/// Type of an extension column, appended to the reads table after the specification's fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtensionType {
+    Utf8,
+    Boolean,
+    UInt32,
+    UInt64,
+    Int64,
+    Float32,
+    Float64,
+    /// A UUID, such as a parent read id, stored as `minknow.uuid`.
+    Uuid,
+}
+
+impl ExtensionType {
+    /// The type an extension column of `data_type` is read back as, see [`ExtensionValue::from_array`].
+    pub fn from_data_type(data_type: &DataType) -> ExtensionType {
+        match data_type {
+            DataType::Boolean => ExtensionType::Boolean,
+            DataType::UInt32 => ExtensionType::UInt32,
+            DataType::UInt64 => ExtensionType::UInt64,
+            DataType::Int64 => ExtensionType::Int64,
+            DataType::Float32 => ExtensionType::Float32,
+            DataType::Float64 => ExtensionType::Float64,
+            DataType::FixedSizeBinary(16) => ExtensionType::Uuid,
+            _ => ExtensionType::Utf8,
+        }
+    }
+
+    /// The nullable field for an extension column of this type. Reads without a value are written as null.
+    pub fn field(&self, name: &str) -> Field {
+        match self {
+            ExtensionType::Utf8 => Field::new(name, DataType::Utf8, true),
+            ExtensionType::Boolean => Field::new(name, DataType::Boolean, true),
+            ExtensionType::UInt32 => Field::new(name, DataType::UInt32, true),
+            ExtensionType::UInt64 => Field::new(name, DataType::UInt64, true),
+            ExtensionType::Int64 => Field::new(name, DataType::Int64, true),
+            ExtensionType::Float32 => Field::new(name, DataType::Float32, true),
+            ExtensionType::Float64 => Field::new(name, DataType::Float64, true),
+            ExtensionType::Uuid => {
+                let mut metadata = HashMap::new();
+                metadata.insert(
+                    "ARROW:extension:name".to_string(),
+                    "minknow.uuid".to_string(),
+                );
+                metadata.insert("ARROW:extension:metadata".to_string(), "".to_string());
+                Field::new(name, DataType::FixedSizeBinary(16), true).with_metadata(metadata)
+            }
+        }
+    }
+}
+
+/// The value of an extension column for a single read.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExtensionValue {
+    Utf8(String),
+    Boolean(bool),
+    UInt32(u32),
+    UInt64(u64),
+    Int64(i64),
+    Float32(f32),
+    Float64(f64),
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::uuid_string"))]
+    Uuid(Uuid),
+}
+
+impl ExtensionValue {
+    /// The type of extension column this value can be written to.
+    pub fn extension_type(&self) -> ExtensionType {
+        match self {
+            ExtensionValue::Utf8(_) => ExtensionType::Utf8,
+            ExtensionValue::Boolean(_) => ExtensionType::Boolean,
+            ExtensionValue::UInt32(_) => ExtensionType::UInt32,
+            ExtensionValue::UInt64(_) => ExtensionType::UInt64,
+            ExtensionValue::Int64(_) => ExtensionType::Int64,
+            ExtensionValue::Float32(_) => ExtensionType::Float32,
+            ExtensionValue::Float64(_) => ExtensionType::Float64,
+            ExtensionValue::Uuid(_) => ExtensionType::Uuid,
+        }
+    }
+
+    /// A single row array holding this value.
+    fn to_array(&self) -> Result<Arc<dyn Array>, Box<dyn Error>> {
+        Ok(match self {
+            ExtensionValue::Utf8(x) => Arc::new(StringArray::from(vec![x.as_str()])),
+            ExtensionValue::Boolean(x) => Arc::new(BooleanArray::from(vec![*x])),
+            ExtensionValue::UInt32(x) => Arc::new(UInt32Array::from(vec![*x])),
+            ExtensionValue::UInt64(x) => Arc::new(UInt64Array::from(vec![*x])),
+            ExtensionValue::Int64(x) => Arc::new(Int64Array::from(vec![*x])),
+            ExtensionValue::Float32(x) => Arc::new(Float32Array::from(vec![*x])),
+            ExtensionValue::Float64(x) => Arc::new(Float64Array::from(vec![*x])),
+            ExtensionValue::Uuid(x) => Arc::new(_build_read_id(*x)?),
+        })
+    }
+
+    /// Read the value of `row` from an extension column. Returns `None` for nulls.
+    ///
+    /// Columns of a type without an `ExtensionValue` variant are surfaced as their display string,
+    /// so unknown columns written by other tools can still be read.
+    pub fn from_array(array: &dyn Array, row: usize) -> Option<ExtensionValue> {
+        if array.is_null(row) {
+            return None;
+        }
+        let any = array.as_any();
+        Some(match array.data_type() {
+            DataType::Utf8 => {
+                ExtensionValue::Utf8(any.downcast_ref::<StringArray>()?.value(row).to_string())
+            }
+            DataType::Boolean => {
+                ExtensionValue::Boolean(any.downcast_ref::<BooleanArray>()?.value(row))
+            }
+            DataType::UInt32 => {
+                ExtensionValue::UInt32(any.downcast_ref::<UInt32Array>()?.value(row))
+            }
+            DataType::UInt64 => {
+                ExtensionValue::UInt64(any.downcast_ref::<UInt64Array>()?.value(row))
+            }
+            DataType::Int64 => ExtensionValue::Int64(any.downcast_ref::<Int64Array>()?.value(row)),
+            DataType::Float32 => {
+                ExtensionValue::Float32(any.downcast_ref::<Float32Array>()?.value(row))
+            }
+            DataType::Float64 => {
+                ExtensionValue::Float64(any.downcast_ref::<Float64Array>()?.value(row))
+            }
+            DataType::FixedSizeBinary(16) => ExtensionValue::Uuid(
+                Uuid::from_slice(any.downcast_ref::<FixedSizeBinaryArray>()?.value(row)).ok()?,
+            ),
+            _ => ExtensionValue::Utf8(
+                ArrayFormatter::try_new(array, &FormatOptions::default())
+                    .ok()?
+                    .value(row)
+                    .to_string(),
+            ),
+        })
+    }
+}
+
+/// Builds the single row array for an extension column, null if the read has no value for it.
+///
+/// # Returns
+///
+/// The array, or an error if the value does not match the type of the column.
+pub fn extension_array(
+    field: &Field,
+    value: Option<&ExtensionValue>,
+) -> Result<Arc<dyn Array>, Box<dyn Error>> {
+    let Some(value) = value else {
+        return Ok(new_null_array(field.data_type(), 1));
+    };
+    let array = value.to_array()?;
+    if array.data_type() != field.data_type() {
+        return Err(format!(
+            "extension column {} is {}, but the value is {}",
+            field.name(),
+            field.data_type(),
+            array.data_type()
+        )
+        .into());
+    }
+    Ok(array)
+}
+
+/// Parses every value of a `Dictionary<Int16, Utf8>` column, such as `pore_type`, `end_reason` or `run_info`.
+///
+/// # Arguments
+///
+/// * `column` - The dictionary column, as read from a reads table.
+///
+/// # Returns
+///
+/// The parsed value of each row, or an error if the column is not a `Dictionary<Int16, Utf8>` or a value fails to parse.
+///
+/// # Examples
+///
+/// ```rust
+/// use arrow::array::{DictionaryArray, Int16Array, StringArray};
+/// use arrow::datatypes::Int16Type;
+/// use podders::reads::{decode_dictionary_column, PoreType};
+/// use std::sync::Arc;
+///
+/// let column = DictionaryArray::<Int16Type>::try_new(
+///     Int16Array::from(vec![1, 0]),
+///     Arc::new(StringArray::from(vec!["RNA004", "R10.4.1"])),
+/// )
+/// .unwrap();
+/// let pores: Vec<PoreType> = decode_dictionary_column(&column).unwrap();
+/// assert_eq!(pores, vec![PoreType::R10, PoreType::Other("RNA004".to_string())]);
+/// ```
+pub fn decode_dictionary_column<T>(column: &dyn Array) -> Result<Vec<T>, Box<dyn Error>>
+where
+    T: FromStr,
+    T::Err: Error + 'static,
+{
+    let dictionary = column
+        .as_any()
+        .downcast_ref::<DictionaryArray<Int16Type>>()
+        .ok_or("column is not a Dictionary<Int16, Utf8>")?;
+    let values = dictionary
+        .values()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .ok_or("dictionary values are not Utf8")?;
+    dictionary
+        .keys()
+        .iter()
+        .map(|key| {
+            let key = key.ok_or("dictionary column contains a null")? as usize;
+            Ok(values.value(key).parse::<T>()?)
+        })
+        .collect()
+}
+
+/// Builds a [`ReadInfo`], deriving `num_samples` from the signal and defaulting everything not set.
+///
+/// Created with [`ReadInfo::builder`]. The defaults are a read on channel 1 well 1 with an unknown
+/// pore type and end reason, an identity calibration, and NaN scalings and median before, as for
+/// reads where these are not known.
+#[derive(Debug, Clone)]
+pub struct ReadInfoBuilder {
+    read: ReadInfo,
+}
+
+impl ReadInfoBuilder {
+    /// The pore type, or its string, e.g. `"R10.4.1"` or `"RNA004"`.
+    pub fn pore_type(mut self, pore_type: impl Into<PoreType>) -> Self {
+        self.read.pore_type = pore_type.into();
+        self
+    }
+
+    pub fn channel(mut self, channel: u16) -> Self {
+        self.read.channel = channel;
+        self
+    }
+
+    pub fn well(mut self, well: u8) -> Self {
+        self.read.well = well;
+        self
+    }
+
+    /// Calibration to picoamps, `pA = (raw + offset) * scale`.
+    pub fn calibration(mut self, offset: f32, scale: f32) -> Self {
+        self.read.calibration_offset = offset;
+        self.read.calibration_scale = scale;
+        self
+    }
+
+    pub fn read_number(mut self, read_number: u32) -> Self {
+        self.read.read_number = read_number;
+        self
+    }
+
+    pub fn start(mut self, start: u64) -> Self {
+        self.read.start = start;
+        self
+    }
+
+    pub fn median_before(mut self, median_before: f32) -> Self {
+        self.read.median_before = median_before;
+        self
+    }
+
+    pub fn tracked_scaling(mut self, shift: f32, scale: f32) -> Self {
+        self.read.tracked_scaling_shift = shift;
+        self.read.tracked_scaling_scale = scale;
+        self
+    }
+
+    pub fn predicted_scaling(mut self, shift: f32, scale: f32) -> Self {
+        self.read.predicted_scaling_shift = shift;
+        self.read.predicted_scaling_scale = scale;
+        self
+    }
+
+    pub fn num_reads_since_mux_change(mut self, num_reads_since_mux_change: u32) -> Self {
+        self.read.num_reads_since_mux_change = num_reads_since_mux_change;
+        self
+    }
+
+    pub fn time_since_mux_change(mut self, time_since_mux_change: f32) -> Self {
+        self.read.time_since_mux_change = time_since_mux_change;
+        self
+    }
+
+    pub fn num_minknow_events(mut self, num_minknow_events: u64) -> Self {
+        self.read.num_minknow_events = num_minknow_events;
+        self
+    }
+
+    /// Set the end reason, or its string, and `end_reason_forced` to its `EndReason::default_forced`.
+    pub fn end_reason(mut self, end_reason: impl Into<EndReason>) -> Self {
+        let end_reason = end_reason.into();
+        self.read.end_reason_forced = end_reason.default_forced();
+        self.read.end_reason = end_reason;
+        self
+    }
+
+    /// Override `end_reason_forced`, call after `end_reason`.
+    pub fn end_reason_forced(mut self, end_reason_forced: bool) -> Self {
+        self.read.end_reason_forced = end_reason_forced;
+        self
+    }
+
+    pub fn open_pore_level(mut self, open_pore_level: f32) -> Self {
+        self.read.open_pore_level = Some(open_pore_level);
+        self
+    }
+
+    /// Set the value of an extension column for this read.
+    pub fn extension(mut self, name: &str, value: ExtensionValue) -> Self {
+        self.read.extensions.insert(name.to_string(), value);
+        self
+    }
+
+    /// Finish the read, checking it with [`ReadInfo::validate`].
+    pub fn build(self) -> Result<ReadInfo, ValidationError> {
+        self.read.validate()?;
+        Ok(self.read)
+    }
+}
 
 /// Constructs a `FixedSizeBinaryArray` from a given UUID.
 ///
@@ -167,6 +702,11 @@ where
 ///
 /// Schema is defined [here](https://github.com/nanoporetech/pod5-file-format/blob/0ba232d6304dd1eebd60d331a6f7c15099dcd04f/docs/tables/reads.toml#L4)
 ///
+/// # Arguments
+///
+/// * `file_identifier` - The identifier of the file the schema is for, recorded in the schema metadata.
+/// * `version` - The pod5 version to create the schema for. Newer versions append fields to the 21 of v0.3.2.
+///
 /// # Returns
 ///
 /// A `Result<Schema, Box<dyn Error>>` that contains either the constructed `Schema` or an error.
@@ -174,14 +714,21 @@ where
 /// # Examples
 ///
 /// ```
-/// use podders::reads::create_reads_arrow_schema; // replace with the actual module name
+/// use podders::reads::create_reads_arrow_schema;
+/// use podders::Pod5Version;
+/// use uuid::Uuid;
 ///
-/// let schema_result = create_reads_arrow_schema();
+/// let schema_result = create_reads_arrow_schema(&Uuid::new_v4(), Pod5Version::V0_3_2);
 /// assert!(schema_result.is_ok());
 /// let schema = schema_result.unwrap();
-/// assert_eq!(schema.fields().len(), 21); // Asserting that 20 fields are defined
+/// assert_eq!(schema.fields().len(), 21); // Asserting that 21 fields are defined
+/// let schema = create_reads_arrow_schema(&Uuid::new_v4(), Pod5Version::V0_3_10).unwrap();
+/// assert_eq!(schema.fields().len(), 22);
 /// ```
-pub fn create_reads_arrow_schema(file_identifier: &Uuid) -> Result<Schema, Box<dyn Error>> {
+pub fn create_reads_arrow_schema(
+    file_identifier: &Uuid,
+    version: Pod5Version,
+) -> Result<Schema, Box<dyn Error>> {
     let signal_field = Arc::new(Field::new("item", DataType::UInt64, true));
     // Define the fields as per the TOML specification
     // Create a metadata map
@@ -192,15 +739,17 @@ pub fn create_reads_arrow_schema(file_identifier: &Uuid) -> Result<Schema, Box<d
     );
     metadata.insert("ARROW:extension:metadata".to_string(), "".to_string());
 
-    let fields = vec![
+    let mut fields = vec![
         Field::new("read_id", DataType::FixedSizeBinary(16), false).with_metadata(metadata), // minknow.uuid as binary
         Field::new("signal", DataType::List(signal_field), false),
         Field::new("channel", DataType::UInt16, false),
         Field::new("well", DataType::UInt8, false),
-        Field::new(
+        Field::new_dict(
             "pore_type",
             DataType::Dictionary(Box::new(DataType::Int16), Box::new(DataType::Utf8)),
             false,
+            PORE_TYPE_DICT_ID,
+            false,
         ),
         Field::new("calibration_offset", DataType::Float32, false),
         Field::new("calibration_scale", DataType::Float32, false),
@@ -214,35 +763,48 @@ pub fn create_reads_arrow_schema(file_identifier: &Uuid) -> Result<Schema, Box<d
         Field::new("num_reads_since_mux_change", DataType::UInt32, false),
         Field::new("time_since_mux_change", DataType::Float32, false),
         Field::new("num_minknow_events", DataType::UInt64, false),
-        Field::new(
+        Field::new_dict(
             "end_reason",
             DataType::Dictionary(Box::new(DataType::Int16), Box::new(DataType::Utf8)),
             false,
+            END_REASON_DICT_ID,
+            false,
         ),
         Field::new("end_reason_forced", DataType::Boolean, false),
-        Field::new(
+        Field::new_dict(
             "run_info",
             DataType::Dictionary(Box::new(DataType::Int16), Box::new(DataType::Utf8)),
             false,
+            RUN_INFO_DICT_ID,
+            false,
         ),
         Field::new("num_samples", DataType::UInt64, false),
     ];
+    if version.has_open_pore_level() {
+        fields.push(Field::new("open_pore_level", DataType::Float32, false));
+    }
     // Define custom metadata
-    let mut metadata = HashMap::new();
-    metadata.insert("MINKNOW:pod5_version".to_string(), POD5_VERSION.to_string());
-    metadata.insert("MINKNOW:software".to_string(), SOFTWARE.to_string());
-    metadata.insert(
-        "MINKNOW:file_identifier".to_string(),
-        file_identifier.to_string(),
-    );
+    let metadata = _schema_metadata(file_identifier, version);
 
     // Create a schema with metadata
     Ok(Schema::new_with_metadata(fields, metadata))
 }
 
+/// A read, and its signal, as pushed to a [`crate::Pod5File`].
+///
+/// With the `serde` feature, the read serializes with its `read_id` as a string, `pore_type` and
+/// `end_reason` as their stored strings, and unset (NaN) measurements as null. An empty signal is
+/// omitted, so `without_signal` serializes just the metadata.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadInfo {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::uuid_string"))]
     pub read_id: Uuid,
     pub pore_type: PoreType,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
     pub signal_: Vec<i16>,
     pub channel: u16,
     pub well: u8,
@@ -250,10 +812,15 @@ pub struct ReadInfo {
     pub calibration_scale: f32,
     pub read_number: u32,
     pub start: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::nan_as_null"))]
     pub median_before: f32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::nan_as_null"))]
     pub tracked_scaling_shift: f32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::nan_as_null"))]
     pub tracked_scaling_scale: f32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::nan_as_null"))]
     pub predicted_scaling_shift: f32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::nan_as_null"))]
     pub predicted_scaling_scale: f32,
     pub num_reads_since_mux_change: u32,
     pub time_since_mux_change: f32,
@@ -262,108 +829,594 @@ pub struct ReadInfo {
     pub end_reason_forced: bool,
     pub run_info: String,
     pub num_samples: u64,
+    /// Open pore level of the read, only written for pod5 versions with the field.
+    /// `None` is written as NaN.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub open_pore_level: Option<f32>,
+    /// Values of the extension columns registered on the writer, by column name.
+    /// Registered columns missing from this map are written as null.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extensions: HashMap<String, ExtensionValue>,
 }
 
+impl ReadInfo {
+    /// Start building a read from its id, the `acquisition_id` of the run info it belongs to, and
+    /// its signal, see [`ReadInfoBuilder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::reads::{EndReason, ReadInfo};
+    /// use uuid::Uuid;
+    ///
+    /// let read = ReadInfo::builder(Uuid::new_v4(), "acquisition_1", vec![1, 2, 3])
+    ///     .channel(12)
+    ///     .calibration(-264.0, 0.187)
+    ///     .end_reason(EndReason::SIGNAL_POSITIVE)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(read.num_samples, 3);
+    /// assert!(!read.end_reason_forced);
+    /// assert_eq!(read.run_info, "acquisition_1");
+    /// assert!(ReadInfo::builder(Uuid::new_v4(), "acquisition_1", vec![]).well(0).build().is_err());
+    /// ```
+    pub fn builder(read_id: Uuid, run_info: &str, signal: Vec<i16>) -> ReadInfoBuilder {
+        ReadInfoBuilder {
+            read: ReadInfo {
+                read_id,
+                pore_type: PoreType::NotSet,
+                num_samples: signal.len() as u64,
+                signal_: signal,
+                channel: 1,
+                well: 1,
+                calibration_offset: 0.0,
+                calibration_scale: 1.0,
+                read_number: 0,
+                start: 0,
+                median_before: f32::NAN,
+                tracked_scaling_shift: f32::NAN,
+                tracked_scaling_scale: f32::NAN,
+                predicted_scaling_shift: f32::NAN,
+                predicted_scaling_scale: f32::NAN,
+                num_reads_since_mux_change: 0,
+                time_since_mux_change: 0.0,
+                num_minknow_events: 0,
+                end_reason: EndReason::UNKNOWN,
+                end_reason_forced: false,
+                run_info: run_info.to_string(),
+                open_pore_level: None,
+                extensions: HashMap::new(),
+            },
+        }
+    }
+
+    /// Start building a read of a run from signal in pA, quantised to ADC values within the run's
+    /// ADC range.
+    ///
+    /// The read is given the run's `acquisition_id` and `calibration`, which can be derived from the run with
+    /// [`Calibration::from_pa_range`]. Samples that do not fit are saturated, and counted in the
+    /// returned [`ClippingStats`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::calibration::Calibration;
+    /// use podders::reads::ReadInfo;
+    /// use podders::run_info::RunInfoData;
+    /// use uuid::Uuid;
+    ///
+    /// let run_info = RunInfoData::builder("acquisition_1").adc_range(-4096, 4095).build().unwrap();
+    /// let calibration = Calibration::from_pa_range(&run_info, -100.0, 400.0).unwrap();
+    /// let pa = [80.0, 95.5, 120.25, 1000.0];
+    /// let (builder, clipping) = ReadInfo::builder_from_pa(Uuid::new_v4(), &pa, calibration, &run_info);
+    /// let read = builder.build().unwrap();
+    /// assert_eq!(read.run_info, "acquisition_1");
+    /// assert_eq!(read.signal_[3], 4095);
+    /// assert_eq!(clipping.above, 1);
+    /// assert!((read.calibration().to_pa(read.signal_[0]) - 80.0).abs() < calibration.scale);
+    /// ```
+    pub fn builder_from_pa(
+        read_id: Uuid,
+        pa: &[f32],
+        calibration: Calibration,
+        run_info: &RunInfoData,
+    ) -> (ReadInfoBuilder, ClippingStats) {
+        let (signal, clipping) = calibration.quantise(pa, run_info.adc_min, run_info.adc_max);
+        let builder = ReadInfo::builder(read_id, &run_info.acquisition_id, signal)
+            .calibration(calibration.offset, calibration.scale);
+        (builder, clipping)
+    }
+
+    /// The read's signal in pA, `(signal_ + calibration_offset) * calibration_scale`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::reads::ReadInfo;
+    /// use uuid::Uuid;
+    ///
+    /// let read = ReadInfo::builder(Uuid::new_v4(), "acquisition_1", vec![264, 364])
+    ///     .calibration(-264.0, 0.5)
+    ///     .build();
+    /// assert_eq!(read.unwrap().signal_pa(), vec![0.0, 50.0]);
+    /// ```
+    pub fn signal_pa(&self) -> Vec<f32> {
+        self.calibration().to_pa_vec(&self.signal_)
+    }
+
+    /// The scaling that normalises the read's pA signal, from where `normalisation` says.
+    ///
+    /// Errors if the read's predicted or tracked scaling is not known, i.e. NaN.
+    pub fn scaling(&self, normalisation: Normalisation) -> Result<Scaling, Box<dyn Error>> {
+        match normalisation {
+            Normalisation::Predicted => {
+                Scaling::new(self.predicted_scaling_shift, self.predicted_scaling_scale)
+            }
+            Normalisation::Tracked => {
+                Scaling::new(self.tracked_scaling_shift, self.tracked_scaling_scale)
+            }
+            Normalisation::MedianMad => Scaling::median_mad(&self.signal_pa()),
+        }
+    }
+
+    /// The read's pA signal normalised with the scaling from where `normalisation` says.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::normalisation::{windows, Normalisation};
+    /// use podders::reads::dummy_read_row;
+    ///
+    /// let mut read = dummy_read_row(None).unwrap();
+    /// read.predicted_scaling_scale = f32::NAN;
+    /// assert!(read.normalised_signal(Normalisation::Predicted).is_err());
+    /// read.predicted_scaling_shift = 90.0;
+    /// read.predicted_scaling_scale = 15.0;
+    /// let signal = read.normalised_signal(Normalisation::Predicted).unwrap();
+    /// for window in windows(&signal, 10000, 500).unwrap() {
+    ///     assert_eq!(window.samples.len(), 10000.min(signal.len()));
+    /// }
+    /// ```
+    pub fn normalised_signal(
+        &self,
+        normalisation: Normalisation,
+    ) -> Result<Vec<f32>, Box<dyn Error>> {
+        let pa = self.signal_pa();
+        let scaling = match normalisation {
+            Normalisation::MedianMad => Scaling::median_mad(&pa)?,
+            _ => self.scaling(normalisation)?,
+        };
+        Ok(scaling.apply(&pa))
+    }
+
+    /// The calibration of the read's signal to pA.
+    pub fn calibration(&self) -> Calibration {
+        Calibration {
+            offset: self.calibration_offset,
+            scale: self.calibration_scale,
+        }
+    }
+
+    /// Check the read for internally inconsistent or out of range fields.
+    ///
+    /// Every problem found is reported in the returned `ValidationError`, rather than stopping at the first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::reads::dummy_read_row;
+    ///
+    /// let mut read = dummy_read_row(None).unwrap();
+    /// assert!(read.validate().is_ok());
+    /// read.well = 5;
+    /// read.calibration_scale = 0.0;
+    /// read.read_number = 3;
+    /// assert_eq!(read.validate().unwrap_err().problems.len(), 3);
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        ReadInfoRef::from(self).validate()
+    }
+
+    /// Check the read against the run it belongs to, i.e. that every signal sample is within
+    /// the run's `[adc_min, adc_max]` range.
+    pub fn validate_against(&self, run_info: &RunInfoData) -> Result<(), ValidationError> {
+        ReadInfoRef::from(self).validate_against(run_info)
+    }
+
+    /// Wall-clock time the read started, from its `start` sample and the run's
+    /// sample rate and acquisition start time.
+    ///
+    /// `None` if the run's sample rate is zero, or the time is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::reads::ReadInfo;
+    /// use podders::run_info::RunInfoData;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use uuid::Uuid;
+    ///
+    /// let acquisition_start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    /// let run_info = RunInfoData::builder("acquisition_1")
+    ///     .start_system_times(acquisition_start, acquisition_start)
+    ///     .sample_rate(4000)
+    ///     .build()
+    ///     .unwrap();
+    /// let read = ReadInfo::builder(Uuid::new_v4(), "acquisition_1", vec![0; 10])
+    ///     .start(6000)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     read.start_time(&run_info),
+    ///     Some(acquisition_start + Duration::from_millis(1500))
+    /// );
+    /// ```
+    pub fn start_time(&self, run_info: &RunInfoData) -> Option<SystemTime> {
+        let sample_rate = u64::from(run_info.sample_rate);
+        if sample_rate == 0 {
+            return None;
+        }
+        // Split into whole seconds first, so that late reads cannot overflow
+        let offset = Duration::from_secs(self.start / sample_rate)
+            + Duration::from_nanos(self.start % sample_rate * 1_000_000_000 / sample_rate);
+        run_info.acquisition_start().checked_add(offset)
+    }
+
+    /// Wall-clock time the read started, in UTC. See [`ReadInfo::start_time`].
+    #[cfg(feature = "chrono")]
+    pub fn start_time_utc(&self, run_info: &RunInfoData) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.start_time(run_info).map(Into::into)
+    }
+
+    /// A copy of the read without its signal, which is left empty.
+    pub fn without_signal(&self) -> ReadInfo {
+        ReadInfo {
+            pore_type: self.pore_type.clone(),
+            signal_: Vec::new(),
+            end_reason: self.end_reason.clone(),
+            run_info: self.run_info.clone(),
+            extensions: self.extensions.clone(),
+            ..*self
+        }
+    }
+
+    /// Check the read's extension values against the extension columns of a reads schema.
+    ///
+    /// # Returns
+    ///
+    /// The names of the values with no matching column, or a value of the wrong type, and the problem with each.
+    pub fn check_extensions(&self, schema: &Schema) -> Vec<(String, String)> {
+        let mut problems = vec![];
+        for (name, value) in &self.extensions {
+            let field = schema
+                .field_with_name(name)
+                .ok()
+                .filter(|_| !SPEC_READ_FIELDS.contains(&name.as_str()));
+            let problem = match field {
+                None => format!("read {}: no extension column named {name}", self.read_id),
+                Some(field)
+                    if field.data_type() != value.extension_type().field(name).data_type() =>
+                {
+                    format!(
+                        "read {}: extension column {name} is {}, but the value is {:?}",
+                        self.read_id,
+                        field.data_type(),
+                        value
+                    )
+                }
+                Some(_) => continue,
+            };
+            problems.push((name.clone(), problem));
+        }
+        problems
+    }
+
+    /// Set `num_samples` to the length of the signal.
+    pub fn fill_num_samples(&mut self) {
+        self.num_samples = self.signal_.len() as u64;
+    }
+
+    /// The read this one was split from, from its [`PARENT_READ_ID`] extension.
+    pub fn parent_read_id(&self) -> Option<Uuid> {
+        match self.extensions.get(PARENT_READ_ID) {
+            Some(ExtensionValue::Uuid(parent)) => Some(*parent),
+            _ => None,
+        }
+    }
+
+    /// Remove `head` samples from the start of the signal and `tail` from the end, e.g. an adapter
+    /// or stall. `start` and `num_samples` are updated to match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::reads::ReadInfo;
+    /// use uuid::Uuid;
+    ///
+    /// let mut read = ReadInfo::builder(Uuid::new_v4(), "acquisition_1", (0..10).collect())
+    ///     .start(100)
+    ///     .build()
+    ///     .unwrap();
+    /// read.trim(2, 3).unwrap();
+    /// assert_eq!(read.signal_, vec![2, 3, 4, 5, 6]);
+    /// assert_eq!((read.start, read.num_samples), (102, 5));
+    /// assert!(read.trim(3, 3).is_err());
+    /// ```
+    pub fn trim(&mut self, head: usize, tail: usize) -> Result<(), ValidationError> {
+        let len = self.signal_.len();
+        if head.saturating_add(tail) > len {
+            return Err(ValidationError {
+                problems: vec![format!(
+                    "cannot trim {head} head and {tail} tail samples from a read of {len} samples"
+                )],
+            });
+        }
+        self.signal_.truncate(len - tail);
+        self.signal_.drain(..head);
+        self.start += head as u64;
+        self.fill_num_samples();
+        Ok(())
+    }
+
+    /// Split the read into child reads at the given sample positions, which must be increasing and
+    /// within the signal.
+    ///
+    /// Each child has a new `read_id`, its part of the signal, and `start` and `num_samples` to match.
+    /// Every other field is kept from the parent, so every child has the parent's `read_number`:
+    /// they are all part of the one read MinKNOW numbered on the channel, and any other number could
+    /// belong to a later read. Siblings are ordered by `start`.
+    ///
+    /// The parent's `read_id` is recorded in the [`PARENT_READ_ID`] extension, or the parent's own
+    /// parent if it was itself split, so children always refer to the read MinKNOW wrote. Write it
+    /// with `WriterOptions::extension_columns` including `(PARENT_READ_ID, ExtensionType::Uuid)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::reads::ReadInfo;
+    /// use uuid::Uuid;
+    ///
+    /// let read = ReadInfo::builder(Uuid::new_v4(), "acquisition_1", (0..10).collect())
+    ///     .start(100)
+    ///     .read_number(7)
+    ///     .build()
+    ///     .unwrap();
+    /// let children = read.split(&[4, 7]).unwrap();
+    /// assert_eq!(children[1].signal_, vec![4, 5, 6]);
+    /// assert_eq!(children[1].start, 104);
+    /// assert!(children.iter().all(|child| child.read_number == 7));
+    /// assert_eq!(children[2].parent_read_id(), Some(read.read_id));
+    /// assert!(read.split(&[7, 4]).is_err());
+    /// ```
+    pub fn split(&self, positions: &[usize]) -> Result<Vec<ReadInfo>, ValidationError> {
+        let len = self.signal_.len();
+        let mut bounds = vec![0];
+        bounds.extend_from_slice(positions);
+        bounds.push(len);
+        if bounds.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(ValidationError {
+                problems: vec![format!(
+                    "split positions {positions:?} must be increasing and within 1..{len}"
+                )],
+            });
+        }
+        let parent = self.parent_read_id().unwrap_or(self.read_id);
+        Ok(bounds
+            .windows(2)
+            .map(|pair| {
+                let mut child = ReadInfo {
+                    read_id: Uuid::new_v4(),
+                    signal_: self.signal_[pair[0]..pair[1]].to_vec(),
+                    start: self.start + pair[0] as u64,
+                    num_samples: (pair[1] - pair[0]) as u64,
+                    ..self.without_signal()
+                };
+                child
+                    .extensions
+                    .insert(PARENT_READ_ID.to_string(), ExtensionValue::Uuid(parent));
+                child
+            })
+            .collect())
+    }
+}
+
+/// A read whose signal is borrowed, e.g. from an acquisition buffer, rather than owned by the read.
 ///
-/// Generates a `RecordBatch` with predefined dummy values for each field defined in the provided schema.
-/// It is particularly useful for testing and simulation purposes where actual data is not required.
+/// Pushing it with `Pod5File::push_read_ref` copies the signal once, straight into an Arrow buffer.
+/// The `signal_` of `read` must be empty, e.g. from `ReadInfo::without_signal`, as only one signal
+/// can be written.
+#[derive(Debug, Clone, Copy)]
+pub struct ReadInfoRef<'a> {
+    pub read: &'a ReadInfo,
+    pub signal: &'a [i16],
+}
+
+impl<'a> ReadInfoRef<'a> {
+    pub fn new(read: &'a ReadInfo, signal: &'a [i16]) -> Self {
+        ReadInfoRef { read, signal }
+    }
+
+    /// See [`ReadInfo::validate`].
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut problems = vec![];
+        if self.read.num_samples != self.signal.len() as u64 {
+            problems.push(format!(
+                "num_samples is {}, but the signal has {} samples",
+                self.read.num_samples,
+                self.signal.len()
+            ));
+        }
+        if !self.read.calibration_scale.is_finite() || self.read.calibration_scale == 0.0 {
+            problems.push(format!(
+                "calibration_scale must be finite and non-zero, got {}",
+                self.read.calibration_scale
+            ));
+        }
+        if !self.read.calibration_offset.is_finite() {
+            problems.push(format!(
+                "calibration_offset must be finite, got {}",
+                self.read.calibration_offset
+            ));
+        }
+        if !(1..=4).contains(&self.read.well) {
+            problems.push(format!("well must be within 1..=4, got {}", self.read.well));
+        }
+        if self.read.run_info.is_empty() {
+            problems.push("run_info must be the acquisition_id of a run info".to_string());
+        }
+        if self.read.channel == 0 {
+            problems.push("channel numbers start at 1, got 0".to_string());
+        }
+        if let PoreType::Other(pore) = &self.read.pore_type {
+            let known = PoreType::from(pore.as_str());
+            if !matches!(known, PoreType::Other(_)) {
+                problems.push(format!(
+                    "pore_type is Other({pore:?}), which must be PoreType::{known:?} instead"
+                ));
+            }
+        }
+        if let EndReason::Other(end_reason) = &self.read.end_reason {
+            let known = EndReason::from(end_reason.as_str());
+            if !matches!(known, EndReason::Other(_)) {
+                problems.push(format!(
+                    "end_reason is Other({end_reason:?}), which must be EndReason::{known:?} instead"
+                ));
+            }
+        }
+        // read_number counts every read of the channel, so is at least the reads since the mux change
+        if self.read.num_reads_since_mux_change > self.read.read_number {
+            problems.push(format!(
+                "num_reads_since_mux_change ({}) is more than read_number ({}), the reads of the channel",
+                self.read.num_reads_since_mux_change, self.read.read_number
+            ));
+        }
+        if self.read.start.checked_add(self.read.num_samples).is_none() {
+            problems.push(format!(
+                "start {} plus num_samples {} overflows",
+                self.read.start, self.read.num_samples
+            ));
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(ValidationError {
+            problems: problems
+                .into_iter()
+                .map(|problem| format!("read {}: {problem}", self.read.read_id))
+                .collect(),
+        })
+    }
+
+    /// See [`ReadInfo::validate_against`].
+    pub fn validate_against(&self, run_info: &RunInfoData) -> Result<(), ValidationError> {
+        let (min, max) = self
+            .signal
+            .iter()
+            .fold((i16::MAX, i16::MIN), |(min, max), &x| {
+                (min.min(x), max.max(x))
+            });
+        if self.signal.is_empty() || (run_info.adc_min <= min && max <= run_info.adc_max) {
+            return Ok(());
+        }
+        Err(ValidationError {
+            problems: vec![format!(
+                "read {}: signal spans [{min}, {max}], outside the adc range [{}, {}] of run info {}",
+                self.read.read_id, run_info.adc_min, run_info.adc_max, run_info.acquisition_id
+            )],
+        })
+    }
+}
+
+impl<'a> From<&'a ReadInfo> for ReadInfoRef<'a> {
+    fn from(read: &'a ReadInfo) -> Self {
+        ReadInfoRef {
+            read,
+            signal: &read.signal_,
+        }
+    }
+}
+
+/// Creates one reads table `RecordBatch` per read, and the signal table batches for their signal.
+///
+/// The values of the dictionary columns (`pore_type`, `end_reason` and `run_info`) are registered
+/// in `dictionaries` first, which fails if they are frozen and a value is new.
 ///
 /// # Arguments
 ///
-/// * `schema` - An `Arc<Schema>` representing the schema of the record batch.
-/// * `read_id` - A `FixedSizeBinaryArray` representing the read IDs.
+/// * `schema` - An `Arc<Schema>` representing the schema of the reads table.
+/// * `reads` - Each read, without its signal, and its signal as an Arrow array.
+/// * `dictionaries` - The dictionaries shared by every batch of the reads table.
+/// * `_signal` - The signal table batches, extended with the signal of each read.
+/// * `signal_schema` - An `Arc<Schema>` representing the schema of the signal table.
+/// * `codec` - The codec the signal is stored with, as in `signal_schema`.
 ///
 /// # Returns
 ///
-/// A `Result<RecordBatch, Box<dyn Error>>` containing the constructed `RecordBatch` or an error.
+/// A `Result<Vec<RecordBatch>, Box<dyn Error>>` containing the reads table batches or an error.
 ///
 /// # Examples
 ///
 /// ```
-/// use arrow::datatypes::{Schema, Field, DataType};
-/// use arrow::array::{FixedSizeBinaryArray, FixedSizeBinaryBuilder};
+/// use arrow::array::Int16Array;
+/// use podders::codec::Uncompressed;
+/// use podders::dictionary::ReadDictionaries;
+/// use podders::reads::{create_read_batches, create_reads_arrow_schema, dummy_read_row};
+/// use podders::signal::signal_schema;
+/// use podders::Pod5Version;
 /// use std::sync::Arc;
 /// use uuid::Uuid;
-/// use podders::reads::{create_reads_arrow_schema, dummy_read_row}; // Replace with actual module name
-///
-/// let schema = create_reads_arrow_schema().unwrap();
-/// let read_id = Uuid::new_v4();
-/// let mut builder = FixedSizeBinaryBuilder::new(16);
-/// builder.append_value(read_id.as_bytes()).unwrap();
-/// let fixed_size_binary_array = builder.finish();
-/// let record_batch = dummy_read_row(Arc::new(schema), fixed_size_binary_array).unwrap();
-/// assert_eq!(record_batch.num_columns(), 21); // Number of fields in the schema
+///
+/// let file_identifier = Uuid::new_v4();
+/// let schema = create_reads_arrow_schema(&file_identifier, Pod5Version::V0_3_2).unwrap();
+/// let signal_schema = signal_schema(&file_identifier, Pod5Version::V0_3_2, &Uncompressed);
+/// let mut read = dummy_read_row(None).unwrap();
+/// let signal = Int16Array::from(std::mem::take(&mut read.signal_));
+/// let mut signal_batches = vec![];
+/// let batches = create_read_batches(
+///     Arc::new(schema),
+///     &[(read, signal)],
+///     &mut ReadDictionaries::new(),
+///     &mut signal_batches,
+///     Arc::new(signal_schema),
+///     &Uncompressed,
+/// )
+/// .unwrap();
+/// assert_eq!(batches[0].num_columns(), 21); // Number of fields in the schema
+/// assert_eq!(signal_batches.len(), 7); // Signal is split into rows of at most 20,000 samples
 /// ```
 pub fn create_read_batches(
     schema: Arc<Schema>,
-    reads: &Vec<ReadInfo>,
+    reads: &[(ReadInfo, Int16Array)],
+    dictionaries: &mut ReadDictionaries,
     _signal: &mut Vec<RecordBatch>,
     signal_schema: Arc<Schema>,
+    codec: &dyn SignalCodec,
 ) -> Result<Vec<RecordBatch>, Box<dyn Error>> {
-    let mut pore_values: Vec<String> = reads.iter().map(|x| x.pore_type.to_string()).collect();
-    let mut end_reasons: Vec<String> = reads.iter().map(|x| x.end_reason.to_string()).collect();
-    let mut run_index: Vec<String> = reads
-        .iter()
-        .map(|x: &ReadInfo| x.run_info.to_string())
-        .collect();
-    pore_values.append(&mut end_reasons);
-    pore_values.append(&mut run_index);
-    let unique_values: HashSet<String> = HashSet::from_iter(pore_values.drain(0..));
-    let pore_values: Vec<String> = Vec::from_iter(unique_values);
-    let dict_map: HashMap<&String, i16> = pore_values
-        .iter()
-        .enumerate()
-        .map(|(index, value)| (value, index as i16))
-        .collect();
-    let dict_values = Arc::new(StringArray::from(pore_values.clone()));
-
+    // Every value is registered before any row is built, so all rows share the final dictionaries
+    for (read, _) in reads {
+        dictionaries.register_read(read)?;
+    }
     let mut batches = vec![];
-    for read in reads {
-        let pt = Arc::new(
-            DictionaryArray::<Int16Type>::try_new(
-                Int16Array::from(vec![*dict_map.get(&read.pore_type.to_string()).unwrap()]),
-                dict_values.clone(),
-            )
-            .unwrap(),
-        );
-        let er = Arc::new(
-            DictionaryArray::<Int16Type>::try_new(
-                Int16Array::from(vec![*dict_map.get(&read.end_reason.to_string()).unwrap()]),
-                dict_values.clone(),
-            )
-            .unwrap(),
-        );
-        let rid = Arc::new(
-            DictionaryArray::<Int16Type>::try_new(
-                Int16Array::from(vec![*dict_map.get(&read.run_info).unwrap()]),
-                dict_values.clone(),
-            )
-            .unwrap(),
-        );
-        batches.push(
-            create_read_row(
-                schema.clone(),
-                read,
-                _signal,
-                signal_schema.clone(),
-                pt,
-                er,
-                rid,
-            )
-            .unwrap(),
-        )
+    for read_signal in reads {
+        batches.push(create_read_row(
+            schema.clone(),
+            read_signal,
+            _signal,
+            signal_schema.clone(),
+            codec,
+            dictionaries.read_columns(&read_signal.0)?,
+        )?)
     }
     Ok(batches)
 }
 
 pub fn create_read_row(
     schema: Arc<Schema>,
-    read: &ReadInfo,
+    (read, samples): &(ReadInfo, Int16Array),
     _signal: &mut Vec<RecordBatch>,
     signal_schema: Arc<Schema>,
-    pore_type: Arc<DictionaryArray<arrow::datatypes::Int16Type>>,
-    end_reason: Arc<DictionaryArray<arrow::datatypes::Int16Type>>,
-    run_info: Arc<DictionaryArray<arrow::datatypes::Int16Type>>,
+    codec: &dyn SignalCodec,
+    [pore_type, end_reason, run_info]: [Arc<DictionaryArray<Int16Type>>; 3],
 ) -> Result<RecordBatch, Box<dyn Error>> {
     // let keys = Int16Array::from(vec![read.pore_type]);
     // let values = StringArray::from(vec!["R10.4.1"]);
@@ -372,7 +1425,7 @@ pub fn create_read_row(
     //     DictionaryArray::try_new(keys, Arc::new(values)).unwrap();
 
     // <-------------------- Handle Signal -------------------->
-    let signal_batches = handle_signal_data(signal_schema, read_id.clone(), &read.signal_)?;
+    let signal_batches = handle_signal_data(signal_schema, read_id.clone(), samples, codec)?;
     let num_signal_rows = signal_batches.len();
     // debug!("Adding this many rows {num_signal_rows}");
     let offset = _signal.len();
@@ -404,67 +1457,65 @@ pub fn create_read_row(
     // let values = StringArray::from(vec!["run_id_1"]);
     // let run_info = DictionaryArray::try_new(keys, Arc::new(values)).unwrap();
     let num_samples = UInt64Array::from(vec![read.num_samples]);
-    let batch2 = RecordBatch::try_new(
-        schema.clone(),
-        vec![
-            Arc::new(read_id.clone()),
-            Arc::new(signal_) as Arc<dyn Array>,
-            Arc::new(channel),
-            Arc::new(well),
-            pore_type,
-            Arc::new(calibration_offset) as Arc<dyn Array>,
-            Arc::new(calibration_scale) as Arc<dyn Array>,
-            Arc::new(read_number) as Arc<dyn Array>,
-            Arc::new(start) as Arc<dyn Array>,
-            Arc::new(median_before) as Arc<dyn Array>,
-            Arc::new(tracked_scaling_scale) as Arc<dyn Array>,
-            Arc::new(tracked_scaling_shift) as Arc<dyn Array>,
-            Arc::new(predicted_scaling_scale) as Arc<dyn Array>,
-            Arc::new(predicted_scaling_shift) as Arc<dyn Array>,
-            Arc::new(num_reads_since_mux_change) as Arc<dyn Array>,
-            Arc::new(time_since_mux_change) as Arc<dyn Array>,
-            Arc::new(num_minknow_events) as Arc<dyn Array>,
-            end_reason,
-            Arc::new(end_reason_forced) as Arc<dyn Array>,
-            run_info,
-            Arc::new(num_samples) as Arc<dyn Array>,
-        ],
-    )
-    .unwrap();
+    let mut columns: Vec<Arc<dyn Array>> = vec![
+        Arc::new(read_id.clone()),
+        Arc::new(signal_) as Arc<dyn Array>,
+        Arc::new(channel),
+        Arc::new(well),
+        pore_type,
+        Arc::new(calibration_offset) as Arc<dyn Array>,
+        Arc::new(calibration_scale) as Arc<dyn Array>,
+        Arc::new(read_number) as Arc<dyn Array>,
+        Arc::new(start) as Arc<dyn Array>,
+        Arc::new(median_before) as Arc<dyn Array>,
+        Arc::new(tracked_scaling_scale) as Arc<dyn Array>,
+        Arc::new(tracked_scaling_shift) as Arc<dyn Array>,
+        Arc::new(predicted_scaling_scale) as Arc<dyn Array>,
+        Arc::new(predicted_scaling_shift) as Arc<dyn Array>,
+        Arc::new(num_reads_since_mux_change) as Arc<dyn Array>,
+        Arc::new(time_since_mux_change) as Arc<dyn Array>,
+        Arc::new(num_minknow_events) as Arc<dyn Array>,
+        end_reason,
+        Arc::new(end_reason_forced) as Arc<dyn Array>,
+        run_info,
+        Arc::new(num_samples) as Arc<dyn Array>,
+    ];
+    // Fields added by newer pod5 versions, present if the schema was created for that version
+    if schema.field_with_name("open_pore_level").is_ok() {
+        let open_pore_level = Float32Array::from(vec![read.open_pore_level.unwrap_or(f32::NAN)]);
+        columns.push(Arc::new(open_pore_level) as Arc<dyn Array>);
+    }
+    // Any remaining fields are extension columns
+    for field in &schema.fields()[columns.len()..] {
+        columns.push(extension_array(field, read.extensions.get(field.name()))?);
+    }
+    let batch2 = RecordBatch::try_new(schema.clone(), columns).unwrap();
     Ok(batch2)
 }
 
 pub fn dummy_read_row(read_id: Option<&str>) -> Result<ReadInfo, Box<dyn Error>> {
-    let signal_data = read_int16_from_file("static/test_signal.bin")?;
+    // Embedded, so this works regardless of the working directory
+    let signal_data = int16_from_ne_bytes(include_bytes!("../static/test_signal.bin"));
     let signal_data: Vec<i16> = signal_data
         .iter()
         .cycle()
         .take(signal_data.len() * 10)
         .cloned()
         .collect();
-    let num_samples = signal_data.len() as u64;
-    let read: ReadInfo = ReadInfo {
-        read_id: Uuid::parse_str(read_id.unwrap_or("56202382-7cda-49e4-9403-2a4f6acc22ab"))?,
-        pore_type: PoreType::R10,
-        signal_: signal_data,
-        channel: 1,
-        well: 1,
-        calibration_offset: -264.0,
-        calibration_scale: 0.187_069_85,
-        read_number: 1,
-        start: 1,
-        median_before: 100.0,
-        tracked_scaling_scale: 1.0,
-        tracked_scaling_shift: 0.1,
-        predicted_scaling_scale: 1.5,
-        predicted_scaling_shift: 0.15,
-        num_reads_since_mux_change: 10,
-        time_since_mux_change: 5.0,
-        num_minknow_events: 100,
-        end_reason: EndReason::SIGNAL_POSITIVE,
-        end_reason_forced: true,
-        run_info: "value1".to_string(),
-        num_samples,
-    };
+    let read_id = Uuid::parse_str(read_id.unwrap_or("56202382-7cda-49e4-9403-2a4f6acc22ab"))?;
+    let read = ReadInfo::builder(read_id, "value1", signal_data)
+        .pore_type(PoreType::R10)
+        .calibration(-264.0, 0.187_069_85)
+        .read_number(12)
+        .start(1)
+        .median_before(100.0)
+        .tracked_scaling(0.1, 1.0)
+        .predicted_scaling(0.15, 1.5)
+        .num_reads_since_mux_change(10)
+        .time_since_mux_change(5.0)
+        .num_minknow_events(100)
+        .end_reason(EndReason::SIGNAL_POSITIVE)
+        .end_reason_forced(true)
+        .build()?;
     Ok(read)
 }
@@ -0,0 +1,237 @@
+//! Resampling of read signal between sample rates.
+//!
+//! A [`Resampler`] converts signal with a band-limited polyphase filter: a windowed sinc low-pass
+//! filter, cut off below the lower of the two Nyquist frequencies, evaluated only at the phases the
+//! output samples need. Reads and run infos are rewritten for the new rate with
+//! [`Resampler::resample_read`] and [`Resampler::resample_run_info`], and a whole pod5 file with
+//! [`resample_file`].
+
+use std::{collections::HashMap, error::Error, f64::consts::PI};
+
+use crate::{
+    reader::Pod5Reader, reads::ReadInfo, run_info::RunInfoData, validation::ValidationPolicy,
+    ContentType, Pod5File, Pod5Version, WriterOptions,
+};
+
+/// Zero crossings of the sinc on each side of the filter centre. More is sharper, and slower.
+const ZERO_CROSSINGS: usize = 16;
+/// Filter cutoff as a fraction of the lower Nyquist frequency, leaving room for the transition band.
+const ROLLOFF: f64 = 0.95;
+
+/// Resamples signal from one sample rate to another.
+///
+/// # Examples
+///
+/// ```
+/// use podders::resample::Resampler;
+///
+/// let resampler = Resampler::new(4000, 5000).unwrap();
+/// let signal: Vec<i16> = (0..400).map(|x| (100.0 * (x as f64 / 20.0).sin()) as i16).collect();
+/// let resampled = resampler.resample(&signal, i16::MIN, i16::MAX);
+/// assert_eq!(resampled.len(), 500);
+/// // The same sine, at 25 rather than 20 samples per radian
+/// assert!((resampled[250] - (100.0 * (10.0f64).sin()) as i16).abs() <= 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    from_rate: u16,
+    to_rate: u16,
+    /// Upsampling factor, `to_rate` over the rates' greatest common divisor.
+    up: usize,
+    /// Downsampling factor, `from_rate` over the rates' greatest common divisor.
+    down: usize,
+    /// Offset of the filter centre, in samples at the upsampled rate.
+    half_len: usize,
+    /// Filter taps of each of the `up` phases, applied to the input from the newest sample back.
+    phases: Vec<Vec<f32>>,
+}
+
+impl Resampler {
+    /// A resampler from `from_rate` to `to_rate`, both in Hz.
+    pub fn new(from_rate: u16, to_rate: u16) -> Result<Self, Box<dyn Error>> {
+        if from_rate == 0 || to_rate == 0 {
+            return Err(format!(
+                "sample rates must be non-zero, got {from_rate} Hz to {to_rate} Hz"
+            )
+            .into());
+        }
+        let divisor = gcd(from_rate as usize, to_rate as usize);
+        let (up, down) = (to_rate as usize / divisor, from_rate as usize / divisor);
+
+        // The prototype filter runs at the upsampled rate, `up` times the input rate
+        let half_len = ZERO_CROSSINGS * up.max(down);
+        let len = 2 * half_len + 1;
+        let cutoff = ROLLOFF / up.max(down) as f64;
+        let mut filter: Vec<f64> = (0..len)
+            .map(|n| {
+                let x = n as f64 - half_len as f64;
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (PI * cutoff * x).sin() / (PI * cutoff * x)
+                };
+                let window = 0.42 - 0.5 * (2.0 * PI * n as f64 / (len - 1) as f64).cos()
+                    + 0.08 * (4.0 * PI * n as f64 / (len - 1) as f64).cos();
+                sinc * window
+            })
+            .collect();
+        // Each phase sees every `up`th tap, so the filter has a gain of `up` to keep the signal level
+        let sum: f64 = filter.iter().sum();
+        for tap in filter.iter_mut() {
+            *tap *= up as f64 / sum;
+        }
+        let phases = (0..up)
+            .map(|phase| {
+                filter
+                    .iter()
+                    .skip(phase)
+                    .step_by(up)
+                    .map(|&tap| tap as f32)
+                    .collect()
+            })
+            .collect();
+        Ok(Resampler {
+            from_rate,
+            to_rate,
+            up,
+            down,
+            half_len,
+            phases,
+        })
+    }
+
+    pub fn from_rate(&self) -> u16 {
+        self.from_rate
+    }
+
+    pub fn to_rate(&self) -> u16 {
+        self.to_rate
+    }
+
+    /// The number of samples `len` input samples resample to.
+    pub fn resampled_len(&self, len: u64) -> u64 {
+        (len * self.up as u64).div_ceil(self.down as u64)
+    }
+
+    /// A sample position, such as a read's `start`, at the new rate, to the nearest sample.
+    pub fn resample_position(&self, position: u64) -> u64 {
+        let (up, down) = (self.up as u128, self.down as u128);
+        ((position as u128 * up + down / 2) / down) as u64
+    }
+
+    /// Resample signal, rounding to the nearest ADC value, and saturating to `[adc_min, adc_max]`
+    /// as the filter can overshoot sharp steps.
+    ///
+    /// The signal is extended past either end with its first and last samples, so a read does not
+    /// ring where it starts and ends. Resampling to the same rate leaves the signal unchanged.
+    pub fn resample(&self, signal: &[i16], adc_min: i16, adc_max: i16) -> Vec<i16> {
+        if self.up == self.down {
+            return signal.to_vec();
+        }
+        let last = signal.len() as i64 - 1;
+        (0..self.resampled_len(signal.len() as u64) as usize)
+            .map(|k| {
+                // Position of the output sample on the upsampled grid, offset by the filter centre,
+                // so tap i of its phase applies to the input sample i before the newest it covers
+                let position = k * self.down + self.half_len;
+                let newest = (position / self.up) as i64;
+                let value: f32 = self.phases[position % self.up]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, tap)| {
+                        tap * f32::from(signal[(newest - i as i64).clamp(0, last) as usize])
+                    })
+                    .sum();
+                value.round().clamp(adc_min.into(), adc_max.into()) as i16
+            })
+            .collect()
+    }
+
+    /// The read at the new rate, with its signal resampled within the ADC range of its run, and
+    /// `start` and `num_samples` to match.
+    pub fn resample_read(&self, read: &ReadInfo, run_info: &RunInfoData) -> ReadInfo {
+        let signal_ = self.resample(&read.signal_, run_info.adc_min, run_info.adc_max);
+        ReadInfo {
+            start: self.resample_position(read.start),
+            num_samples: signal_.len() as u64,
+            signal_,
+            ..read.clone()
+        }
+    }
+
+    /// The run info at the new rate, refusing runs not sampled at the rate resampled from.
+    ///
+    /// Updates `sample_rate`, and the `sample_frequency` context tag if the run has one.
+    pub fn resample_run_info(&self, run_info: &RunInfoData) -> Result<RunInfoData, Box<dyn Error>> {
+        if run_info.sample_rate != self.from_rate {
+            return Err(format!(
+                "run {} is sampled at {} Hz, not {} Hz",
+                run_info.acquisition_id, run_info.sample_rate, self.from_rate
+            )
+            .into());
+        }
+        let mut run_info = run_info.clone();
+        run_info.sample_rate = self.to_rate;
+        if run_info.sample_frequency().is_some() {
+            run_info.set_sample_frequency(self.to_rate.into());
+        }
+        Ok(run_info)
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Write a copy of a pod5 file with every read resampled to `to_rate` Hz.
+///
+/// Each read is resampled from the sample rate of its run. The copy keeps the input's signal codec,
+/// extension columns and the custom metadata of each table, and is written as the supported pod5
+/// version nearest the input's, see [`Pod5Version::nearest`]. The copy is validated with
+/// `validation`, e.g. `ValidationPolicy::Lenient` for an input that was written leniently.
+pub fn resample_file(
+    input: &str,
+    output: &str,
+    to_rate: u16,
+    validation: ValidationPolicy,
+) -> Result<(), Box<dyn Error>> {
+    let mut reader = Pod5Reader::open(input)?;
+    let mut table_schema_metadata = HashMap::new();
+    for table in [
+        ContentType::RunInfoTable,
+        ContentType::ReadsTable,
+        ContentType::SignalTable,
+    ] {
+        table_schema_metadata.insert(table, reader.custom_metadata(table)?);
+    }
+    let options = WriterOptions {
+        validation,
+        pod5_version: Pod5Version::nearest(reader.pod5_version()),
+        table_schema_metadata,
+        extension_columns: reader.extension_columns()?,
+        signal_codec: Some(reader.signal_codec()?),
+        ..Default::default()
+    };
+    let mut pod5 = Pod5File::with_options(output, options)?;
+    let mut resamplers = HashMap::new();
+    for run_info in reader.run_infos()? {
+        let resampler = Resampler::new(run_info.sample_rate, to_rate)?;
+        pod5.push_run_info(resampler.resample_run_info(&run_info)?)?;
+        resamplers.insert(run_info.acquisition_id.clone(), (resampler, run_info));
+    }
+    pod5.write_run_info_to_ipc()?;
+    for read in reader.reads()? {
+        let (resampler, run_info) = resamplers
+            .get(&read.run_info)
+            .ok_or_else(|| format!("read {} has no run info", read.read_id))?;
+        pod5.push_read(resampler.resample_read(&read, run_info))?;
+    }
+    pod5.write_reads_to_ipc()?;
+    pod5.write_signal_to_ipc()?;
+    pod5.write_footer()?;
+    Ok(())
+}
@@ -3,7 +3,14 @@ use arrow::{
     datatypes::{DataType, Field, Schema, TimeUnit},
     record_batch::RecordBatch,
 };
-use std::{collections::HashMap, error::Error, sync::Arc};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use uuid::Uuid;
 
 use arrow::array::{
@@ -11,8 +18,10 @@ use arrow::array::{
     UInt16Array,
 };
 
-use crate::{POD5_VERSION, SOFTWARE};
+use crate::{_schema_metadata, validation::ValidationError, Pod5Version, SOFTWARE};
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RunInfoData {
     pub acquisition_id: String,
     pub acquisition_start_time: i64, // Timestamp in milliseconds
@@ -36,6 +45,526 @@ pub struct RunInfoData {
     pub tracking_id: HashMap<String, String>, // Assuming similar structure as context_tags
 }
 
+/// The documented MinKNOW keys of [`RunInfoData::context_tags`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContextTag {
+    BarcodingEnabled,
+    BasecallConfigFilename,
+    ExperimentDurationSet,
+    ExperimentType,
+    LocalBasecalling,
+    PackageVersion,
+    SampleFrequency,
+    SelectedSpeedBasesPerSecond,
+    SequencingKit,
+}
+
+impl ContextTag {
+    /// The key as stored in the run info table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContextTag::BarcodingEnabled => "barcoding_enabled",
+            ContextTag::BasecallConfigFilename => "basecall_config_filename",
+            ContextTag::ExperimentDurationSet => "experiment_duration_set",
+            ContextTag::ExperimentType => "experiment_type",
+            ContextTag::LocalBasecalling => "local_basecalling",
+            ContextTag::PackageVersion => "package_version",
+            ContextTag::SampleFrequency => "sample_frequency",
+            ContextTag::SelectedSpeedBasesPerSecond => "selected_speed_bases_per_second",
+            ContextTag::SequencingKit => "sequencing_kit",
+        }
+    }
+}
+
+impl fmt::Display for ContextTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsRef<str> for ContextTag {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// The documented MinKNOW keys of [`RunInfoData::tracking_id`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrackingKey {
+    AsicId,
+    AsicIdEeprom,
+    AsicTemp,
+    AsicVersion,
+    ConfigurationVersion,
+    DeviceId,
+    DeviceType,
+    DistributionVersion,
+    ExpScriptPurpose,
+    ExpStartTime,
+    FlowCellId,
+    FlowCellProductCode,
+    GuppyVersion,
+    HeatsinkTemp,
+    Hostname,
+    OperatingSystem,
+    ProtocolGroupId,
+    ProtocolRunId,
+    ProtocolsVersion,
+    RunId,
+    SampleId,
+    UsbConfig,
+    Version,
+}
+
+impl TrackingKey {
+    /// The keys downstream tools rely on, checked by [`RunInfoData::validate_tags`].
+    pub const REQUIRED: [TrackingKey; 5] = [
+        TrackingKey::DeviceId,
+        TrackingKey::DeviceType,
+        TrackingKey::ExpStartTime,
+        TrackingKey::FlowCellId,
+        TrackingKey::RunId,
+    ];
+
+    /// The key as stored in the run info table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrackingKey::AsicId => "asic_id",
+            TrackingKey::AsicIdEeprom => "asic_id_eeprom",
+            TrackingKey::AsicTemp => "asic_temp",
+            TrackingKey::AsicVersion => "asic_version",
+            TrackingKey::ConfigurationVersion => "configuration_version",
+            TrackingKey::DeviceId => "device_id",
+            TrackingKey::DeviceType => "device_type",
+            TrackingKey::DistributionVersion => "distribution_version",
+            TrackingKey::ExpScriptPurpose => "exp_script_purpose",
+            TrackingKey::ExpStartTime => "exp_start_time",
+            TrackingKey::FlowCellId => "flow_cell_id",
+            TrackingKey::FlowCellProductCode => "flow_cell_product_code",
+            TrackingKey::GuppyVersion => "guppy_version",
+            TrackingKey::HeatsinkTemp => "heatsink_temp",
+            TrackingKey::Hostname => "hostname",
+            TrackingKey::OperatingSystem => "operating_system",
+            TrackingKey::ProtocolGroupId => "protocol_group_id",
+            TrackingKey::ProtocolRunId => "protocol_run_id",
+            TrackingKey::ProtocolsVersion => "protocols_version",
+            TrackingKey::RunId => "run_id",
+            TrackingKey::SampleId => "sample_id",
+            TrackingKey::UsbConfig => "usb_config",
+            TrackingKey::Version => "version",
+        }
+    }
+}
+
+impl fmt::Display for TrackingKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsRef<str> for TrackingKey {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Builds a [`RunInfoData`], defaulting everything not set.
+///
+/// Created with [`RunInfoData::builder`]. The defaults are a 5 kHz run over the full `i16` adc range,
+/// started at the unix epoch, written by podders, with empty descriptive strings and tags.
+#[derive(Debug, Clone)]
+pub struct RunInfoDataBuilder {
+    run_info: RunInfoData,
+}
+
+impl RunInfoDataBuilder {
+    /// Acquisition and protocol start time, both in milliseconds since the unix epoch.
+    pub fn start_times(mut self, acquisition_start_time: i64, protocol_start_time: i64) -> Self {
+        self.run_info.acquisition_start_time = acquisition_start_time;
+        self.run_info.protocol_start_time = protocol_start_time;
+        self
+    }
+
+    /// Acquisition and protocol start time, from anything convertible to a `SystemTime`,
+    /// such as a `chrono::DateTime`. Stored to the millisecond.
+    pub fn start_system_times(
+        self,
+        acquisition_start: impl Into<SystemTime>,
+        protocol_start: impl Into<SystemTime>,
+    ) -> Self {
+        self.start_times(
+            millis_since_epoch(acquisition_start.into()),
+            millis_since_epoch(protocol_start.into()),
+        )
+    }
+
+    pub fn adc_range(mut self, adc_min: i16, adc_max: i16) -> Self {
+        self.run_info.adc_min = adc_min;
+        self.run_info.adc_max = adc_max;
+        self
+    }
+
+    /// A context tag, by a [`ContextTag`] or any other key.
+    pub fn context_tag(mut self, key: impl AsRef<str>, value: &str) -> Self {
+        self.run_info
+            .context_tags
+            .insert(key.as_ref().to_string(), value.to_string());
+        self
+    }
+
+    pub fn experiment_name(mut self, experiment_name: &str) -> Self {
+        self.run_info.experiment_name = experiment_name.to_string();
+        self
+    }
+
+    pub fn flow_cell(mut self, flow_cell_id: &str, flow_cell_product_code: &str) -> Self {
+        self.run_info.flow_cell_id = flow_cell_id.to_string();
+        self.run_info.flow_cell_product_code = flow_cell_product_code.to_string();
+        self
+    }
+
+    pub fn protocol(mut self, protocol_name: &str, protocol_run_id: &str) -> Self {
+        self.run_info.protocol_name = protocol_name.to_string();
+        self.run_info.protocol_run_id = protocol_run_id.to_string();
+        self
+    }
+
+    pub fn sample_id(mut self, sample_id: &str) -> Self {
+        self.run_info.sample_id = sample_id.to_string();
+        self
+    }
+
+    pub fn sample_rate(mut self, sample_rate: u16) -> Self {
+        self.run_info.sample_rate = sample_rate;
+        self
+    }
+
+    pub fn sequencing_kit(mut self, sequencing_kit: &str) -> Self {
+        self.run_info.sequencing_kit = sequencing_kit.to_string();
+        self
+    }
+
+    pub fn sequencer_position(mut self, position: &str, position_type: &str) -> Self {
+        self.run_info.sequencer_position = position.to_string();
+        self.run_info.sequencer_position_type = position_type.to_string();
+        self
+    }
+
+    pub fn software(mut self, software: &str) -> Self {
+        self.run_info.software = software.to_string();
+        self
+    }
+
+    pub fn system(mut self, system_name: &str, system_type: &str) -> Self {
+        self.run_info.system_name = system_name.to_string();
+        self.run_info.system_type = system_type.to_string();
+        self
+    }
+
+    /// A tracking id entry, by a [`TrackingKey`] or any other key.
+    pub fn tracking_id(mut self, key: impl AsRef<str>, value: &str) -> Self {
+        self.run_info
+            .tracking_id
+            .insert(key.as_ref().to_string(), value.to_string());
+        self
+    }
+
+    /// Finish the run info, checking it with [`RunInfoData::validate`].
+    pub fn build(self) -> Result<RunInfoData, ValidationError> {
+        self.run_info.validate()?;
+        Ok(self.run_info)
+    }
+}
+
+impl RunInfoData {
+    /// Start building a run info from its `acquisition_id`, see [`RunInfoDataBuilder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::run_info::RunInfoData;
+    ///
+    /// let run_info = RunInfoData::builder("acquisition_1")
+    ///     .sample_rate(4000)
+    ///     .flow_cell("FAT12345", "FLO-MIN114")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(run_info.sample_rate, 4000);
+    /// assert!(RunInfoData::builder("").build().is_err());
+    /// ```
+    pub fn builder(acquisition_id: &str) -> RunInfoDataBuilder {
+        RunInfoDataBuilder {
+            run_info: RunInfoData {
+                acquisition_id: acquisition_id.to_string(),
+                acquisition_start_time: 0,
+                adc_max: i16::MAX,
+                adc_min: i16::MIN,
+                context_tags: HashMap::new(),
+                experiment_name: String::new(),
+                flow_cell_id: String::new(),
+                flow_cell_product_code: String::new(),
+                protocol_name: String::new(),
+                protocol_run_id: String::new(),
+                protocol_start_time: 0,
+                sample_id: String::new(),
+                sample_rate: 5000,
+                sequencing_kit: String::new(),
+                sequencer_position: String::new(),
+                sequencer_position_type: String::new(),
+                software: SOFTWARE.to_string(),
+                system_name: String::new(),
+                system_type: String::new(),
+                tracking_id: HashMap::new(),
+            },
+        }
+    }
+
+    /// Wall-clock time the acquisition started.
+    pub fn acquisition_start(&self) -> SystemTime {
+        system_time_from_millis(self.acquisition_start_time)
+    }
+
+    /// Wall-clock time the protocol started.
+    pub fn protocol_start(&self) -> SystemTime {
+        system_time_from_millis(self.protocol_start_time)
+    }
+
+    /// Wall-clock time the acquisition started, in UTC.
+    #[cfg(feature = "chrono")]
+    pub fn acquisition_start_utc(&self) -> chrono::DateTime<chrono::Utc> {
+        self.acquisition_start().into()
+    }
+
+    /// Wall-clock time the protocol started, in UTC.
+    #[cfg(feature = "chrono")]
+    pub fn protocol_start_utc(&self) -> chrono::DateTime<chrono::Utc> {
+        self.protocol_start().into()
+    }
+
+    /// A context tag by its documented key.
+    pub fn context_tag(&self, tag: ContextTag) -> Option<&str> {
+        self.context_tags.get(tag.as_str()).map(String::as_str)
+    }
+
+    pub fn set_context_tag(&mut self, tag: ContextTag, value: impl Into<String>) {
+        self.context_tags
+            .insert(tag.as_str().to_string(), value.into());
+    }
+
+    /// A tracking id entry by its documented key.
+    pub fn tracking(&self, key: TrackingKey) -> Option<&str> {
+        self.tracking_id.get(key.as_str()).map(String::as_str)
+    }
+
+    pub fn set_tracking(&mut self, key: TrackingKey, value: impl Into<String>) {
+        self.tracking_id
+            .insert(key.as_str().to_string(), value.into());
+    }
+
+    /// A numeric context tag, `None` if it is missing or not a number.
+    fn numeric_tag(&self, tag: ContextTag) -> Option<u32> {
+        self.context_tag(tag)?.parse().ok()
+    }
+
+    /// A flag context tag, stored as `1`/`0` by MinKNOW, `None` if it is missing or not a flag.
+    fn flag_tag(&self, tag: ContextTag) -> Option<bool> {
+        parse_flag(self.context_tag(tag)?)
+    }
+
+    pub fn basecall_config_filename(&self) -> Option<&str> {
+        self.context_tag(ContextTag::BasecallConfigFilename)
+    }
+
+    pub fn experiment_type(&self) -> Option<&str> {
+        self.context_tag(ContextTag::ExperimentType)
+    }
+
+    /// Sample rate recorded in the context tags, in Hz.
+    pub fn sample_frequency(&self) -> Option<u32> {
+        self.numeric_tag(ContextTag::SampleFrequency)
+    }
+
+    pub fn set_sample_frequency(&mut self, sample_frequency: u32) {
+        self.set_context_tag(ContextTag::SampleFrequency, sample_frequency.to_string());
+    }
+
+    pub fn selected_speed_bases_per_second(&self) -> Option<u32> {
+        self.numeric_tag(ContextTag::SelectedSpeedBasesPerSecond)
+    }
+
+    pub fn set_selected_speed_bases_per_second(&mut self, speed: u32) {
+        self.set_context_tag(ContextTag::SelectedSpeedBasesPerSecond, speed.to_string());
+    }
+
+    /// Run length the experiment was set up with, in minutes.
+    pub fn experiment_duration_set(&self) -> Option<u32> {
+        self.numeric_tag(ContextTag::ExperimentDurationSet)
+    }
+
+    pub fn set_experiment_duration_set(&mut self, minutes: u32) {
+        self.set_context_tag(ContextTag::ExperimentDurationSet, minutes.to_string());
+    }
+
+    pub fn barcoding_enabled(&self) -> Option<bool> {
+        self.flag_tag(ContextTag::BarcodingEnabled)
+    }
+
+    pub fn set_barcoding_enabled(&mut self, enabled: bool) {
+        self.set_context_tag(ContextTag::BarcodingEnabled, u8::from(enabled).to_string());
+    }
+
+    pub fn local_basecalling(&self) -> Option<bool> {
+        self.flag_tag(ContextTag::LocalBasecalling)
+    }
+
+    pub fn set_local_basecalling(&mut self, enabled: bool) {
+        self.set_context_tag(ContextTag::LocalBasecalling, u8::from(enabled).to_string());
+    }
+
+    pub fn run_id(&self) -> Option<&str> {
+        self.tracking(TrackingKey::RunId)
+    }
+
+    pub fn device_type(&self) -> Option<&str> {
+        self.tracking(TrackingKey::DeviceType)
+    }
+
+    /// Check the context tags and tracking id against the documented MinKNOW keys.
+    ///
+    /// Reports every [`TrackingKey::REQUIRED`] key that is missing, typed context tags whose
+    /// values do not parse, and a `sample_frequency` tag that disagrees with `sample_rate`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::run_info::{RunInfoData, TrackingKey};
+    ///
+    /// let mut run_info = RunInfoData::builder("acquisition_1").build().unwrap();
+    /// assert_eq!(run_info.validate_tags().unwrap_err().problems.len(), 5);
+    /// for key in TrackingKey::REQUIRED {
+    ///     run_info.set_tracking(key, "set");
+    /// }
+    /// run_info.set_sample_frequency(4000);
+    /// assert_eq!(run_info.validate_tags().unwrap_err().problems.len(), 1);
+    /// ```
+    pub fn validate_tags(&self) -> Result<(), ValidationError> {
+        let mut problems = vec![];
+        for key in TrackingKey::REQUIRED {
+            if self.tracking(key).is_none() {
+                problems.push(format!("tracking_id is missing {key}"));
+            }
+        }
+        for tag in [
+            ContextTag::SampleFrequency,
+            ContextTag::SelectedSpeedBasesPerSecond,
+            ContextTag::ExperimentDurationSet,
+        ] {
+            if let Some(value) = self.context_tag(tag) {
+                if u32::from_str(value).is_err() {
+                    problems.push(format!("context tag {tag} is not a number, got {value}"));
+                }
+            }
+        }
+        for tag in [ContextTag::BarcodingEnabled, ContextTag::LocalBasecalling] {
+            if let Some(value) = self.context_tag(tag) {
+                if parse_flag(value).is_none() {
+                    problems.push(format!("context tag {tag} is not a flag, got {value}"));
+                }
+            }
+        }
+        if let Some(sample_frequency) = self.sample_frequency() {
+            if sample_frequency != u32::from(self.sample_rate) {
+                problems.push(format!(
+                    "context tag sample_frequency ({sample_frequency}) disagrees with sample_rate ({})",
+                    self.sample_rate
+                ));
+            }
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(ValidationError {
+            problems: problems
+                .into_iter()
+                .map(|problem| format!("run info {}: {problem}", self.acquisition_id))
+                .collect(),
+        })
+    }
+
+    /// Check the run info for internally inconsistent or out of range fields.
+    ///
+    /// Every problem found is reported in the returned `ValidationError`, rather than stopping at the first.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut problems = vec![];
+        if self.acquisition_id.is_empty() {
+            problems.push("acquisition_id must not be empty".to_string());
+        }
+        if self.adc_min >= self.adc_max {
+            problems.push(format!(
+                "adc_min ({}) must be less than adc_max ({})",
+                self.adc_min, self.adc_max
+            ));
+        }
+        if self.sample_rate == 0 {
+            problems.push("sample_rate must be non-zero".to_string());
+        }
+        if self.acquisition_start_time < 0 {
+            problems.push(format!(
+                "acquisition_start_time must not be negative, got {}",
+                self.acquisition_start_time
+            ));
+        }
+        if self.protocol_start_time < 0 {
+            problems.push(format!(
+                "protocol_start_time must not be negative, got {}",
+                self.protocol_start_time
+            ));
+        }
+        if self.protocol_start_time > self.acquisition_start_time {
+            problems.push(format!(
+                "protocol_start_time ({}) is after acquisition_start_time ({})",
+                self.protocol_start_time, self.acquisition_start_time
+            ));
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(ValidationError {
+            problems: problems
+                .into_iter()
+                .map(|problem| format!("run info {}: {problem}", self.acquisition_id))
+                .collect(),
+        })
+    }
+}
+
+/// A MinKNOW flag value, `1`/`0`, or `true`/`false`.
+fn parse_flag(value: &str) -> Option<bool> {
+    match value {
+        "1" | "true" => Some(true),
+        "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Milliseconds since the unix epoch, negative before it, as stored in the run info table.
+fn millis_since_epoch(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => after.as_millis() as i64,
+        Err(before) => -(before.duration().as_millis() as i64),
+    }
+}
+
+/// The inverse of `millis_since_epoch`.
+fn system_time_from_millis(millis: i64) -> SystemTime {
+    let offset = Duration::from_millis(millis.unsigned_abs());
+    if millis < 0 {
+        UNIX_EPOCH - offset
+    } else {
+        UNIX_EPOCH + offset
+    }
+}
+
 fn convert_hashmap_to_maparray(map: &HashMap<String, String>) -> arrow::error::Result<MapArray> {
     let mut map_builder = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
 
@@ -133,14 +662,11 @@ fn _tags_field(name: &str) -> Field {
     )
 }
 
-pub fn run_info_schema(file_identifier: &Uuid) -> Result<Schema, Box<dyn Error>> {
-    let mut metadata: HashMap<String, String> = HashMap::new();
-    metadata.insert("MINKNOW:pod5_version".to_string(), POD5_VERSION.to_string());
-    metadata.insert("MINKNOW:software".to_string(), SOFTWARE.to_string());
-    metadata.insert(
-        "MINKNOW:file_identifier".to_string(),
-        file_identifier.to_string(),
-    );
+pub fn run_info_schema(
+    file_identifier: &Uuid,
+    version: Pod5Version,
+) -> Result<Schema, Box<dyn Error>> {
+    let metadata = _schema_metadata(file_identifier, version);
 
     // Create a schema with metadata
     let schema_with_metadata = Schema::new_with_metadata(
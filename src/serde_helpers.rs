@@ -0,0 +1,60 @@
+//! Helpers for the optional `serde` support, enabled with the `serde` cargo feature.
+//!
+//! Values are serialized the way they are stored in a pod5 file where possible, so that
+//! serialized metadata stays readable, and stable across formats.
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+use std::{fmt::Display, str::FromStr};
+use uuid::Uuid;
+
+/// A value as its string form, e.g. `PoreType` and `EndReason` as stored in the dictionaries.
+pub(crate) fn serialize_display<T: Display, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// A value parsed from its string form.
+pub(crate) fn deserialize_from_str<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(D::Error::custom)
+}
+
+/// A `Uuid` as its hyphenated string, whether or not the format is human readable.
+pub(crate) mod uuid_string {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(uuid: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(uuid, serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Uuid, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+/// An `f32` that is NaN when unset, as null, which formats such as JSON cannot represent otherwise.
+pub(crate) mod nan_as_null {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_nan() {
+            serializer.serialize_none()
+        } else {
+            serializer.serialize_some(value)
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        Ok(Option::<f32>::deserialize(deserializer)?.unwrap_or(f32::NAN))
+    }
+}
@@ -1,5 +1,5 @@
 use arrow::{
-    array::{Array, Int16Builder, LargeListBuilder, UInt32Array},
+    array::{Array, Int16Array, UInt32Array},
     datatypes::{DataType, Field, Schema},
     record_batch::RecordBatch,
 };
@@ -8,7 +8,7 @@ use std::io::{self, Read};
 use std::{collections::HashMap, error::Error, sync::Arc};
 use uuid::Uuid;
 
-use crate::{POD5_VERSION, SOFTWARE};
+use crate::{_schema_metadata, codec::SignalCodec, Pod5Version};
 
 /// Maximum signal data in a row
 const MAX_SIGNAL: usize = 20000;
@@ -17,24 +17,25 @@ pub fn read_int16_from_file(filename: &str) -> io::Result<Vec<i16>> {
     let mut f = File::open(filename)?;
     let mut buffer = Vec::new();
     f.read_to_end(&mut buffer)?;
+    Ok(int16_from_ne_bytes(&buffer))
+}
 
+/// Convert raw bytes to signal samples.
+pub fn int16_from_ne_bytes(buffer: &[u8]) -> Vec<i16> {
     // Assuming the system has the same endianness as the file was written with
-    let data: Vec<i16> = buffer
+    buffer
         .chunks_exact(2)
         .map(|chunk| i16::from_ne_bytes([chunk[0], chunk[1]]))
-        .collect();
-    Ok(data)
+        .collect()
 }
 
-pub fn signal_schema(file_identifier: &Uuid) -> Schema {
-    let mut metadata: HashMap<String, String> = HashMap::new();
-
-    metadata.insert("MINKNOW:pod5_version".to_string(), POD5_VERSION.to_string());
-    metadata.insert("MINKNOW:software".to_string(), SOFTWARE.to_string());
-    metadata.insert(
-        "MINKNOW:file_identifier".to_string(),
-        file_identifier.to_string(),
-    );
+/// Schema of the signal table, with the signal stored by `codec`.
+pub fn signal_schema(
+    file_identifier: &Uuid,
+    version: Pod5Version,
+    codec: &dyn SignalCodec,
+) -> Schema {
+    let metadata = _schema_metadata(file_identifier, version);
 
     let mut read_metadata = HashMap::new();
     read_metadata.insert(
@@ -47,45 +48,35 @@ pub fn signal_schema(file_identifier: &Uuid) -> Schema {
         vec![
             Field::new("read_id", DataType::FixedSizeBinary(16), false)
                 .with_metadata(read_metadata), // minknow.uuid as binary
-            Field::new(
-                "signal",
-                DataType::LargeList(Arc::new(Field::new("item", DataType::Int16, true))),
-                false,
-            ), // Large list of int16 for signal
+            codec.field(),
             Field::new("samples", DataType::UInt32, false), // uint32 for samples
         ],
         metadata,
     )
 }
 
+/// Split a read's signal into rows of the signal table, each encoded with `codec`.
 pub fn handle_signal_data(
     schema: Arc<Schema>,
     read_id: arrow::array::FixedSizeBinaryArray,
-    signal_vec: &[i16],
+    signal: &Int16Array,
+    codec: &dyn SignalCodec,
 ) -> Result<Vec<RecordBatch>, Box<dyn Error>> {
-    // Create dummy data
-    // Create a LargeListBuilder
     let mut batches = vec![];
-    // Append a list to the LargeListBuilder
-
-    for chunk in signal_vec.chunks(MAX_SIGNAL) {
-        let mut signal_builder = LargeListBuilder::new(Int16Builder::new());
-        signal_builder.values().append_slice(chunk);
-        signal_builder.append(true);
-        let samples = UInt32Array::from(vec![chunk.len() as u32]);
-        let r_id = read_id.clone();
-        let signal = Arc::new(signal_builder.finish());
+    for offset in (0..signal.len()).step_by(MAX_SIGNAL) {
+        let len = MAX_SIGNAL.min(signal.len() - offset);
+        // Slicing shares the signal buffer, so the samples are not copied per row
+        let chunk = codec.encode(&signal.slice(offset, len))?;
+        let samples = UInt32Array::from(vec![len as u32]);
         let batch = RecordBatch::try_new(
             schema.clone(),
             vec![
-                Arc::new(r_id) as Arc<dyn Array>,
-                signal.clone() as Arc<dyn Array>,
+                Arc::new(read_id.clone()) as Arc<dyn Array>,
+                chunk,
                 Arc::new(samples) as Arc<dyn Array>,
             ],
         )?;
         batches.push(batch)
     }
-    // Create a RecordBatch
-
     Ok(batches)
 }
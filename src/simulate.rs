@@ -0,0 +1,314 @@
+//! Simulation of read signal from a nucleotide sequence, for testing downstream tools.
+//!
+//! A [`KmerModel`] gives the mean current level of each k-mer. The [`Simulator`] steps through the
+//! k-mers of a sequence, holding each level for a random dwell time, adds Gaussian noise, and
+//! quantises the result into a [`ReadInfo`] ready for `Pod5File::push_read`. Simulations with a
+//! [`SimulatorOptions::seed`] are reproducible.
+
+use std::{collections::HashMap, error::Error, f64::consts::PI};
+
+use uuid::{Builder, Uuid};
+
+use crate::{
+    calibration::{Calibration, ClippingStats},
+    reads::{EndReason, ReadInfo},
+    run_info::RunInfoData,
+};
+
+/// The level of a single k-mer, in the units of its model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KmerLevel {
+    pub mean: f32,
+    /// Standard deviation of the signal around the mean, if the model has one.
+    pub stdv: Option<f32>,
+}
+
+/// The levels of every k-mer of a pore, such as the k-mer models published by Oxford Nanopore.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KmerModel {
+    k: usize,
+    levels: HashMap<String, KmerLevel>,
+}
+
+impl KmerModel {
+    /// A model from the level of each k-mer. Every k-mer must be the same, non-zero, length.
+    pub fn new(
+        levels: impl IntoIterator<Item = (String, KmerLevel)>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let levels: HashMap<String, KmerLevel> = levels
+            .into_iter()
+            .map(|(kmer, level)| (kmer.to_ascii_uppercase(), level))
+            .collect();
+        let k = levels.keys().next().map_or(0, String::len);
+        if k == 0 {
+            return Err("a k-mer model needs at least one non-empty k-mer".into());
+        }
+        if let Some(kmer) = levels.keys().find(|kmer| kmer.len() != k) {
+            return Err(format!("k-mer {kmer} is not {k} bases long, as the others are").into());
+        }
+        Ok(KmerModel { k, levels })
+    }
+
+    /// Parse a whitespace separated level table, of a k-mer, its mean level and optionally its
+    /// standard deviation per line. A header line, and blank lines, are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use podders::simulate::KmerModel;
+    ///
+    /// let model = KmerModel::from_tsv("kmer\tlevel_mean\nAA\t-1.5\nAC\t0.25\n").unwrap();
+    /// assert_eq!(model.k(), 2);
+    /// assert_eq!(model.level("ac").unwrap().mean, 0.25);
+    /// assert!(model.level("AG").is_none());
+    /// ```
+    pub fn from_tsv(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut levels = vec![];
+        for (number, line) in text.lines().enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (kmer, mean, stdv) = match fields.as_slice() {
+                [] => continue,
+                [kmer, mean] => (kmer, mean, None),
+                [kmer, mean, stdv] => (kmer, mean, Some(stdv)),
+                _ => {
+                    return Err(format!(
+                        "line {} of the level table has {} fields, not 2 or 3",
+                        number + 1,
+                        fields.len()
+                    )
+                    .into())
+                }
+            };
+            let Ok(mean) = mean.parse() else {
+                if number == 0 {
+                    continue;
+                }
+                return Err(format!("line {} has level {mean:?}, not a number", number + 1).into());
+            };
+            let stdv = stdv.map(|x| x.parse()).transpose()?;
+            levels.push((kmer.to_string(), KmerLevel { mean, stdv }));
+        }
+        KmerModel::new(levels)
+    }
+
+    /// Length of the k-mers of the model.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The level of a k-mer, regardless of case.
+    pub fn level(&self, kmer: &str) -> Option<KmerLevel> {
+        self.levels.get(&kmer.to_ascii_uppercase()).copied()
+    }
+}
+
+/// How the [`Simulator`] generates signal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatorOptions {
+    /// Mean translocation speed, which with the run's sample rate sets the mean dwell per k-mer.
+    pub bases_per_second: f32,
+    /// Shape of the Erlang distribution of dwell times. Higher is more regular, 1 is exponential.
+    pub dwell_shape: u32,
+    /// Standard deviation of the noise, in the units of the model, for k-mers without their own.
+    pub noise_stdv: f32,
+    /// Converts model levels to pA, as `level * level_scale + level_shift`. Recorded as the
+    /// predicted and tracked scaling of simulated reads, so normalising them recovers model units.
+    pub level_shift: f32,
+    pub level_scale: f32,
+    /// Calibration of simulated reads. If `None`, the run's ADC range is spread over the model's
+    /// pA levels, padded by their span either side.
+    pub calibration: Option<Calibration>,
+    /// Seed of the random number generator, for reproducible simulations, read ids included.
+    /// If `None`, the generator is seeded randomly and read ids are random v4 uuids, so they are
+    /// unique across simulators.
+    pub seed: Option<u64>,
+}
+
+impl Default for SimulatorOptions {
+    /// Simulates 400 bases per second, with levels in pA.
+    fn default() -> Self {
+        SimulatorOptions {
+            bases_per_second: 400.0,
+            dwell_shape: 3,
+            noise_stdv: 1.5,
+            level_shift: 0.0,
+            level_scale: 1.0,
+            calibration: None,
+            seed: None,
+        }
+    }
+}
+
+/// A simulated read, with the ground truth of how it was generated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedRead {
+    pub read: ReadInfo,
+    /// Samples spent in each k-mer of the sequence, in order.
+    pub dwells: Vec<usize>,
+    /// Samples that did not fit the run's ADC range.
+    pub clipping: ClippingStats,
+}
+
+/// Generates reads from sequences, see the [module documentation](self).
+///
+/// Reads are numbered in order, and placed one after another on channel 1, starting at sample 0.
+///
+/// # Examples
+///
+/// ```
+/// use podders::run_info::RunInfoData;
+/// use podders::simulate::{KmerModel, Simulator, SimulatorOptions};
+///
+/// let model = KmerModel::from_tsv("A 80\nC 95\nG 110\nT 125").unwrap();
+/// let run_info = RunInfoData::builder("acquisition_1").adc_range(-4096, 4095).build().unwrap();
+/// let mut simulator = Simulator::new(model, SimulatorOptions::default());
+/// let simulated = simulator.simulate("ACGTTGCA", &run_info).unwrap();
+/// assert_eq!(simulated.dwells.len(), 8);
+/// assert_eq!(simulated.read.num_samples, simulated.dwells.iter().sum::<usize>() as u64);
+/// assert_eq!(simulated.read.run_info, "acquisition_1");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    model: KmerModel,
+    options: SimulatorOptions,
+    rng: SplitMix64,
+    read_number: u32,
+    start: u64,
+}
+
+impl Simulator {
+    pub fn new(model: KmerModel, options: SimulatorOptions) -> Self {
+        Simulator {
+            rng: SplitMix64(
+                options
+                    .seed
+                    .unwrap_or_else(|| Uuid::new_v4().as_u64_pair().0),
+            ),
+            model,
+            options,
+            read_number: 0,
+            start: 0,
+        }
+    }
+
+    /// Simulate the read of a sequence, in a run. Every k-mer of the sequence must be in the model.
+    pub fn simulate(
+        &mut self,
+        sequence: &str,
+        run_info: &RunInfoData,
+    ) -> Result<SimulatedRead, Box<dyn Error>> {
+        let options = &self.options;
+        if options.bases_per_second.is_nan()
+            || options.bases_per_second <= 0.0
+            || options.dwell_shape == 0
+        {
+            return Err(format!(
+                "bases_per_second ({}) and dwell_shape ({}) must be positive",
+                options.bases_per_second, options.dwell_shape
+            )
+            .into());
+        }
+        let k = self.model.k();
+        if !sequence.is_ascii() || sequence.len() < k {
+            return Err(format!("sequence must be at least {k} ASCII bases").into());
+        }
+        let levels = (0..=sequence.len() - k)
+            .map(|i| {
+                let kmer = &sequence[i..i + k];
+                self.model
+                    .level(kmer)
+                    .ok_or_else(|| format!("k-mer {kmer} is not in the model"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mean_dwell = f64::from(run_info.sample_rate) / f64::from(options.bases_per_second);
+        let mut pa = vec![];
+        let mut dwells = vec![];
+        for level in levels {
+            let mut dwell = 0.0;
+            for _ in 0..options.dwell_shape {
+                dwell += self
+                    .rng
+                    .exponential(mean_dwell / f64::from(options.dwell_shape));
+            }
+            let dwell = (dwell.round() as usize).max(1);
+            let stdv = level.stdv.unwrap_or(options.noise_stdv);
+            for _ in 0..dwell {
+                let sample = level.mean + stdv * self.rng.normal() as f32;
+                pa.push(sample * options.level_scale + options.level_shift);
+            }
+            dwells.push(dwell);
+        }
+
+        let calibration = match options.calibration {
+            Some(calibration) => calibration,
+            None => self.default_calibration(run_info)?,
+        };
+        let read_id = match options.seed {
+            Some(_) => Builder::from_random_bytes(self.rng.next_u128().to_le_bytes()).into_uuid(),
+            None => Uuid::new_v4(),
+        };
+        let (builder, clipping) = ReadInfo::builder_from_pa(read_id, &pa, calibration, run_info);
+        let read = builder
+            .read_number(self.read_number)
+            .start(self.start)
+            .predicted_scaling(options.level_shift, options.level_scale)
+            .tracked_scaling(options.level_shift, options.level_scale)
+            .end_reason(EndReason::SIGNAL_POSITIVE)
+            .build()?;
+        self.read_number += 1;
+        self.start += read.num_samples;
+        Ok(SimulatedRead {
+            read,
+            dwells,
+            clipping,
+        })
+    }
+
+    /// The run's ADC range spread over the model's pA levels, padded by their span either side.
+    fn default_calibration(&self, run_info: &RunInfoData) -> Result<Calibration, Box<dyn Error>> {
+        let options = &self.options;
+        let (min, max) = self
+            .model
+            .levels
+            .values()
+            .map(|level| level.mean * options.level_scale + options.level_shift)
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), x| {
+                (min.min(x), max.max(x))
+            });
+        let pad = (max - min).max(1.0);
+        Ok(Calibration::from_pa_range(run_info, min - pad, max + pad)?)
+    }
+}
+
+/// The SplitMix64 generator, small and fast, and plenty random enough for simulated noise.
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn next_u128(&mut self) -> u128 {
+        (u128::from(self.next_u64()) << 64) | u128::from(self.next_u64())
+    }
+
+    /// Uniform in `(0, 1]`.
+    fn uniform(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    fn exponential(&mut self, mean: f64) -> f64 {
+        -self.uniform().ln() * mean
+    }
+
+    /// Standard normal, by the Box-Muller transform.
+    fn normal(&mut self) -> f64 {
+        (-2.0 * self.uniform().ln()).sqrt() * (2.0 * PI * self.uniform()).cos()
+    }
+}
@@ -0,0 +1,61 @@
+//! Validation of data pushed into a [`crate::Pod5File`] before it is written.
+//!
+//! Problems found while pushing or writing are collected as human readable strings,
+//! and then enforced according to the [`ValidationPolicy`] the writer was created with.
+
+use log::warn;
+use std::{error::Error, fmt};
+
+/// How the writer reacts to data that would produce an inconsistent pod5 file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationPolicy {
+    /// Refuse the offending data, returning a [`ValidationError`].
+    #[default]
+    Strict,
+    /// Log a warning for each problem via `log`, and carry on.
+    Lenient,
+}
+
+/// What the writer does when a read is pushed with a `read_id` that has already been pushed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Refuse the duplicate read, returning a [`ValidationError`].
+    #[default]
+    Reject,
+    /// Replace the previously pushed read with the new one.
+    Overwrite,
+    /// Keep the previously pushed read, and drop the new one.
+    Skip,
+}
+
+/// Every problem found while validating a piece of data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "validation failed: {}", self.problems.join("; "))
+    }
+}
+
+impl Error for ValidationError {}
+
+impl ValidationPolicy {
+    /// Enforce this policy on the given problems. No problems is always `Ok`.
+    pub(crate) fn enforce(&self, problems: Vec<String>) -> Result<(), ValidationError> {
+        if problems.is_empty() {
+            return Ok(());
+        }
+        match self {
+            ValidationPolicy::Strict => Err(ValidationError { problems }),
+            ValidationPolicy::Lenient => {
+                for problem in problems {
+                    warn!("{problem}");
+                }
+                Ok(())
+            }
+        }
+    }
+}